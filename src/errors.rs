@@ -0,0 +1,186 @@
+use std::fmt;
+use std::io;
+
+/// Errors from the crate's fallible operations
+#[derive(Debug)]
+pub enum Error {
+    /// Text didn't match the format it was parsed as
+    Parse(String),
+    /// A cell didn't fit within a bounded grid
+    OutOfBounds(OutOfBounds),
+    /// A grid had to be bounded for the operation but wasn't
+    Unbounded,
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// A world would have more cells than allowed
+    TooLarge {
+        width: usize,
+        height: usize,
+        limit: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(reason) => write!(f, "parse error: {}", reason),
+            Error::OutOfBounds(e) => write!(f, "{}", e),
+            Error::Unbounded => write!(f, "grid must be bounded"),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::TooLarge {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "a {}x{} world is larger than the limit of {} cells",
+                width, height, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::OutOfBounds(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<OutOfBounds> for Error {
+    fn from(e: OutOfBounds) -> Self {
+        Error::OutOfBounds(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Error for a cell that doesn't fit in a bounded grid
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OutOfBounds {
+    /// The rejected cell
+    pub cell: (usize, usize),
+    /// Width and height of the grid that rejected it
+    pub bounds: (usize, usize),
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cell ({}, {}) is outside a {}x{} grid",
+            self.cell.0, self.cell.1, self.bounds.0, self.bounds.1
+        )
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+/// Error for a run stopped because its population grew past a limit
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PopulationExceeded {
+    /// Generation the world was stopped at
+    pub generation: usize,
+    /// Number of live cells at that generation
+    pub population: usize,
+    /// Most live cells the run allowed
+    pub limit: usize,
+}
+
+impl fmt::Display for PopulationExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "population of {} at generation {} exceeds the limit of {}",
+            self.population, self.generation, self.limit
+        )
+    }
+}
+
+impl std::error::Error for PopulationExceeded {}
+
+/// Errors from operations combining several worlds
+#[derive(PartialEq, Eq, Debug)]
+pub enum WorldError {
+    /// The worlds follow different rules
+    RuleMismatch,
+    /// The worlds' sizes don't line up along the edge they're joined on
+    SizeMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The combined world would have more cells than allowed
+    TooLarge {
+        width: usize,
+        height: usize,
+        limit: usize,
+    },
+}
+
+impl fmt::Display for WorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WorldError::RuleMismatch => write!(f, "worlds follow different rules"),
+            WorldError::SizeMismatch { expected, found } => write!(
+                f,
+                "worlds don't line up: expected a side of {} cells, found {}",
+                expected, found
+            ),
+            WorldError::TooLarge {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "a {}x{} world is larger than the limit of {} cells",
+                width, height, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorldError {}
+
+/// Errors from `World::render_gif`
+#[cfg(feature = "gif")]
+#[derive(Debug)]
+pub enum GifError {
+    /// The frames would be wider or taller than a GIF allows
+    TooLarge,
+    /// Encoding or writing the file failed
+    Encoding(gif::EncodingError),
+}
+
+#[cfg(feature = "gif")]
+impl fmt::Display for GifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GifError::TooLarge => write!(f, "world too large for a GIF"),
+            GifError::Encoding(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "gif")]
+impl std::error::Error for GifError {}
+
+#[cfg(feature = "gif")]
+impl From<gif::EncodingError> for GifError {
+    fn from(e: gif::EncodingError) -> Self {
+        GifError::Encoding(e)
+    }
+}
+
+#[cfg(feature = "gif")]
+impl From<io::Error> for GifError {
+    fn from(e: io::Error) -> Self {
+        GifError::Encoding(e.into())
+    }
+}
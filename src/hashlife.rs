@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+
+use crate::{Grid, Rule, MOORE};
+
+/// Index of a node in a `HashLifeWorld`'s arena
+type NodeId = u32;
+
+/// A square of 2^level by 2^level cells
+///
+/// Level 0 nodes are single cells and have no children; every other node is
+/// split into four quadrants one level down.
+#[derive(Clone, Copy)]
+struct Node {
+    level: u8,
+    nw: NodeId,
+    ne: NodeId,
+    sw: NodeId,
+    se: NodeId,
+    population: usize,
+}
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Most generations a world can advance in all, past which the quadtree
+/// would outgrow the `i64` coordinates it's placed with
+const MAX_GENERATION: u64 = 1 << 60;
+
+/// Coordinates a world's cells must stay below, for the same reason
+const MAX_COORD: usize = 1 << 60;
+
+/// A world on the infinite plane, stored as a quadtree in the style of
+/// HashLife
+///
+/// Identical subtrees are stored once and the future of every node is
+/// remembered, so large, repetitive patterns can be advanced by huge numbers
+/// of generations at once with `step_n`. Remembered results are never
+/// forgotten, so memory use only ever grows.
+pub struct HashLifeWorld {
+    rule: Rule,
+    nodes: Vec<Node>,
+    joined: HashMap<(NodeId, NodeId, NodeId, NodeId), NodeId>,
+    advanced: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    origin: (i64, i64),
+    generation: usize,
+}
+
+impl HashLifeWorld {
+    /// Returns a world holding the live cells of `grid`, at the same
+    /// coordinates, following `rule`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rule` doesn't look at the full Moore neighborhood or brings
+    /// cells with no live neighbors to life, as the latter would fill the
+    /// infinite plane, or if a live cell has a coordinate of 2^60 or more.
+    pub fn new(grid: &Grid, rule: Rule) -> Self {
+        assert_eq!(rule.mask, MOORE, "HashLife needs a Moore neighborhood rule");
+        assert!(!rule.bin[0], "HashLife can't run rules with B0");
+        assert!(
+            grid.grid.iter().all(|&(x, y)| x < MAX_COORD && y < MAX_COORD),
+            "HashLifeWorld can't hold cells at coordinates of 2^60 or more"
+        );
+        let leaf = |population| Node {
+            level: 0,
+            nw: DEAD,
+            ne: DEAD,
+            sw: DEAD,
+            se: DEAD,
+            population,
+        };
+        let mut world = HashLifeWorld {
+            rule,
+            nodes: vec![leaf(0), leaf(1)],
+            joined: HashMap::new(),
+            advanced: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (0, 0),
+            generation: 0,
+        };
+        let size = grid
+            .grid
+            .iter()
+            .map(|&(x, y)| x.max(y) + 1)
+            .max()
+            .unwrap_or(1);
+        let mut level = 3;
+        while (1usize << level) < size {
+            level += 1;
+        }
+        world.root = world.empty(level);
+        for &(x, y) in grid.grid.iter() {
+            world.root = world.set(world.root, x, y);
+        }
+        world
+    }
+
+    /// Returns the number of generations the world has advanced
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the number of live cells
+    pub fn population(&self) -> usize {
+        self.nodes[self.root as usize].population
+    }
+
+    /// Returns the live cells as an unbounded grid, in the coordinates the
+    /// world was created with
+    ///
+    /// Cells that have moved to negative coordinates are left out.
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = Grid::new(None);
+        self.collect(self.root, self.origin, &mut grid);
+        grid
+    }
+
+    /// Advances the world by one generation
+    pub fn step(&mut self) {
+        self.step_n(1);
+    }
+
+    /// Advances the world by `n` generations
+    ///
+    /// `n` is split into powers of two and each is taken as a single jump,
+    /// so the cost grows with the size of the pattern's history rather than
+    /// with `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world would pass generation 2^60.
+    pub fn step_n(&mut self, n: usize) {
+        let generation = self
+            .generation
+            .checked_add(n)
+            .filter(|&generation| generation as u64 <= MAX_GENERATION)
+            .expect("HashLifeWorld can't advance past generation 2^60");
+        for j in 0..usize::BITS {
+            if (n >> j) & 1 == 1 {
+                self.jump(j as u8);
+            }
+        }
+        self.generation = generation;
+    }
+
+    /// Advances the root by 2^j generations
+    fn jump(&mut self, j: u8) {
+        // The result of advancing a node is its central half, and patterns
+        // spread by at most a cell per generation, so keep the pattern within
+        // the central quarter and the node big enough for 2^j generations of
+        // spread to stay inside that half.
+        while !self.is_padded(self.root) {
+            self.expand();
+        }
+        self.expand();
+        while self.node(self.root).level < j + 3 {
+            self.expand();
+        }
+        let level = self.node(self.root).level;
+        self.root = self.advance(self.root, j);
+        let shift = 1i64 << (level - 2);
+        self.origin = (self.origin.0 + shift, self.origin.1 + shift);
+    }
+
+    /// Returns whether every live cell of `id` lies in its central half
+    fn is_padded(&self, id: NodeId) -> bool {
+        let node = self.node(id);
+        if node.level < 2 {
+            return node.population == 0;
+        }
+        let (nw, ne, sw, se) = (
+            self.node(node.nw),
+            self.node(node.ne),
+            self.node(node.sw),
+            self.node(node.se),
+        );
+        let outer = [
+            nw.nw, nw.ne, nw.sw, ne.nw, ne.ne, ne.se, sw.nw, sw.sw, sw.se, se.ne, se.sw, se.se,
+        ];
+        outer.iter().all(|&id| self.node(id).population == 0)
+    }
+
+    /// Doubles the size of the root, keeping it centered
+    fn expand(&mut self) {
+        let root = self.node(self.root);
+        let e = self.empty(root.level - 1);
+        let nw = self.join(e, e, e, root.nw);
+        let ne = self.join(e, e, root.ne, e);
+        let sw = self.join(e, root.sw, e, e);
+        let se = self.join(root.se, e, e, e);
+        self.root = self.join(nw, ne, sw, se);
+        let shift = 1i64 << (root.level - 1);
+        self.origin = (self.origin.0 - shift, self.origin.1 - shift);
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    /// Returns the canonical node with the given quadrants
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        if let Some(&id) = self.joined.get(&(nw, ne, sw, se)) {
+            return id;
+        }
+        let population = [nw, ne, sw, se]
+            .iter()
+            .map(|&id| self.node(id).population)
+            .sum();
+        let node = Node {
+            level: self.node(nw).level + 1,
+            nw,
+            ne,
+            sw,
+            se,
+            population,
+        };
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.joined.insert((nw, ne, sw, se), id);
+        id
+    }
+
+    /// Returns the empty node of `level`
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    /// Returns `id` with the cell at `(x, y)`, relative to its top left
+    /// corner, made live
+    fn set(&mut self, id: NodeId, x: usize, y: usize) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return ALIVE;
+        }
+        let half = 1 << (node.level - 1);
+        let (mut nw, mut ne, mut sw, mut se) = (node.nw, node.ne, node.sw, node.se);
+        match (x < half, y < half) {
+            (true, true) => nw = self.set(nw, x, y),
+            (false, true) => ne = self.set(ne, x - half, y),
+            (true, false) => sw = self.set(sw, x, y - half),
+            (false, false) => se = self.set(se, x - half, y - half),
+        }
+        self.join(nw, ne, sw, se)
+    }
+
+    /// Returns whether the cell at `(x, y)`, relative to the top left corner
+    /// of `id`, is live
+    fn get(&self, id: NodeId, x: usize, y: usize) -> bool {
+        let node = self.node(id);
+        if node.level == 0 {
+            return id == ALIVE;
+        }
+        let half = 1 << (node.level - 1);
+        match (x < half, y < half) {
+            (true, true) => self.get(node.nw, x, y),
+            (false, true) => self.get(node.ne, x - half, y),
+            (true, false) => self.get(node.sw, x, y - half),
+            (false, false) => self.get(node.se, x - half, y - half),
+        }
+    }
+
+    fn collect(&self, id: NodeId, (x, y): (i64, i64), grid: &mut Grid) {
+        let node = self.node(id);
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            if x >= 0 && y >= 0 {
+                grid.insert(&(x as usize, y as usize));
+            }
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        self.collect(node.nw, (x, y), grid);
+        self.collect(node.ne, (x + half, y), grid);
+        self.collect(node.sw, (x, y + half), grid);
+        self.collect(node.se, (x + half, y + half), grid);
+    }
+
+    /// Returns the central quadrant of `id`
+    fn center(&mut self, id: NodeId) -> NodeId {
+        let node = self.node(id);
+        let (nw, ne, sw, se) = (
+            self.node(node.nw),
+            self.node(node.ne),
+            self.node(node.sw),
+            self.node(node.se),
+        );
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    /// Returns the node straddling the border between `w` and `e`
+    fn horizontal(&mut self, w: NodeId, e: NodeId) -> NodeId {
+        let (w, e) = (self.node(w), self.node(e));
+        self.join(w.ne, e.nw, w.se, e.sw)
+    }
+
+    /// Returns the node straddling the border between `n` and `s`
+    fn vertical(&mut self, n: NodeId, s: NodeId) -> NodeId {
+        let (n, s) = (self.node(n), self.node(s));
+        self.join(n.sw, n.se, s.nw, s.ne)
+    }
+
+    /// Returns the central half of `id` advanced by 2^j generations, where
+    /// `j` is at most the node's level minus two
+    fn advance(&mut self, id: NodeId, j: u8) -> NodeId {
+        if let Some(&result) = self.advanced.get(&(id, j)) {
+            return result;
+        }
+        let node = self.node(id);
+        let result = if node.population == 0 {
+            self.empty(node.level - 1)
+        } else if node.level == 2 {
+            self.advance_base(id)
+        } else {
+            let (nw, ne, sw, se) = (node.nw, node.ne, node.sw, node.se);
+            let n01 = self.horizontal(nw, ne);
+            let n10 = self.vertical(nw, sw);
+            let n11 = self.center(id);
+            let n12 = self.vertical(ne, se);
+            let n21 = self.horizontal(sw, se);
+            let parts = [nw, n01, ne, n10, n11, n12, sw, n21, se];
+
+            // At full speed both halves of the jump happen here; otherwise
+            // the nine parts are only cropped and all the time passes below.
+            let full_speed = j == node.level - 2;
+            let mut r = [DEAD; 9];
+            for (r, &part) in r.iter_mut().zip(parts.iter()) {
+                *r = if full_speed {
+                    self.advance(part, j - 1)
+                } else {
+                    self.center(part)
+                };
+            }
+            let next = if full_speed { j - 1 } else { j };
+            let c00 = self.join(r[0], r[1], r[3], r[4]);
+            let c01 = self.join(r[1], r[2], r[4], r[5]);
+            let c10 = self.join(r[3], r[4], r[6], r[7]);
+            let c11 = self.join(r[4], r[5], r[7], r[8]);
+            let nw = self.advance(c00, next);
+            let ne = self.advance(c01, next);
+            let sw = self.advance(c10, next);
+            let se = self.advance(c11, next);
+            self.join(nw, ne, sw, se)
+        };
+        self.advanced.insert((id, j), result);
+        result
+    }
+
+    /// Returns the central 2x2 cells of the 4x4 node `id` one generation on
+    fn advance_base(&mut self, id: NodeId) -> NodeId {
+        let mut next = [DEAD; 4];
+        for (i, next) in next.iter_mut().enumerate() {
+            let (x, y) = (1 + i % 2, 1 + i / 2);
+            let mut state = 0;
+            // Same layout as get_state: bit dx + 3 * dy for the neighbor at
+            // (x + dx - 1, y + dy - 1).
+            for dx in 0..3 {
+                for dy in 0..3 {
+                    if self.get(id, x + dx - 1, y + dy - 1) {
+                        state += 1 << (dx + 3 * dy);
+                    }
+                }
+            }
+            if self.rule.bin[state] {
+                *next = ALIVE;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+}
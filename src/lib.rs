@@ -1,15 +1,101 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use bit_vec::BitVec;
 use num::bigint::BigUint;
-use rand::Rng;
+#[cfg(feature = "random")]
+use rand::rngs::StdRng;
+#[cfg(feature = "random")]
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "terminal")]
 use rustty::ui::Widget;
-use rustty::CellAccessor;
+#[cfg(feature = "terminal")]
+use rustty::{CellAccessor, HasSize};
 
-type Cell = (usize, usize);
-type CellSet = HashSet<Cell>;
+mod errors;
+mod hashlife;
+#[cfg(feature = "serde")]
+mod session;
+mod signed;
 
-#[derive(PartialEq, Eq, Debug)]
+pub use crate::errors::{Error, OutOfBounds, PopulationExceeded, WorldError};
+pub use crate::hashlife::HashLifeWorld;
+#[cfg(feature = "serde")]
+pub use crate::session::Session;
+pub use crate::signed::SignedGrid;
+#[cfg(feature = "gif")]
+pub use crate::errors::GifError;
+
+type Coords = (usize, usize);
+type CellSet = HashSet<Coords>;
+
+/// The position of a cell, `x` cells right of and `y` cells down from the
+/// top left corner
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Cell {
+    /// Returns the cell `dx` cells right of and `dy` cells down from this one,
+    /// or `None` if that would take a coordinate below zero or past
+    /// `usize::MAX`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::Cell;
+    /// let origin = Cell { x: 0, y: 0 };
+    /// assert_eq!(origin.checked_offset(1, 0), Some(Cell { x: 1, y: 0 }));
+    /// assert_eq!(origin.checked_offset(-1, 0), None);
+    /// ```
+    pub fn checked_offset(self, dx: isize, dy: isize) -> Option<Cell> {
+        let offset = |c: usize, d: isize| {
+            if d < 0 {
+                c.checked_sub(d.unsigned_abs())
+            } else {
+                c.checked_add(d as usize)
+            }
+        };
+        Some(Cell {
+            x: offset(self.x, dx)?,
+            y: offset(self.y, dy)?,
+        })
+    }
+
+    /// Returns the up to 8 cells surrounding this one, top row first, leaving
+    /// out those that would have a negative coordinate
+    pub fn neighbors(self) -> impl Iterator<Item = Cell> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(move |(dx, dy)| self.checked_offset(dx, dy))
+    }
+}
+
+impl From<Coords> for Cell {
+    fn from((x, y): Coords) -> Self {
+        Cell { x, y }
+    }
+}
+
+impl From<Cell> for Coords {
+    fn from(cell: Cell) -> Self {
+        (cell.x, cell.y)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Grid {
     grid: CellSet,
     bounds: Option<(usize, usize)>,
@@ -17,296 +103,5939 @@ pub struct Grid {
 
 impl Grid {
     fn new(b: Option<(usize, usize)>) -> Self {
-        match b {
-            None => Grid {
-                grid: CellSet::new(),
-                bounds: b,
-            },
-            Some((w, h)) => Grid {
-                grid: CellSet::with_capacity(w * h),
-                bounds: b,
-            },
+        // Live cells are usually a small share of a bounded grid, so nothing
+        // is reserved up front.
+        Grid {
+            grid: CellSet::new(),
+            bounds: b,
         }
     }
-    fn insert(&mut self, cell: &Cell) {
+
+    /// Returns an empty grid stretching infinitely right and down
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut grid = hemoglobin::Grid::unbounded();
+    /// grid.insert(&(1_000_000, 3));
+    /// assert_eq!(grid.population(), 1);
+    /// ```
+    pub fn unbounded() -> Self {
+        Grid::new(None)
+    }
+
+    /// Returns an empty grid of `width` by `height` cells
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut grid = hemoglobin::Grid::bounded(4, 4);
+    /// grid.insert(&(3, 3));
+    /// grid.insert(&(4, 0)); // out of bounds, ignored
+    /// assert_eq!(grid.population(), 1);
+    /// ```
+    pub fn bounded(width: usize, height: usize) -> Self {
+        Grid::new(Some((width, height)))
+    }
+
+    /// Makes `cell` live, unless it lies outside the grid's bounds
+    pub fn insert(&mut self, cell: &Coords) {
+        let _ = self.try_insert(*cell);
+    }
+
+    /// Makes `cell` live, or fails if it lies outside the grid's bounds
+    pub fn try_insert(&mut self, cell: Coords) -> Result<(), OutOfBounds> {
         match self.bounds {
-            None => {
-                self.grid.insert(*cell);
+            Some((w, h)) if cell.0 >= w || cell.1 >= h => Err(OutOfBounds {
+                cell,
+                bounds: (w, h),
+            }),
+            _ => {
+                self.grid.insert(cell);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns whether `cell` is live
+    pub fn contains(&self, cell: &Coords) -> bool {
+        self.grid.contains(cell)
+    }
+
+    /// Returns whether `cell` is live
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from(vec![" #"]);
+    /// assert!(grid.is_alive((1, 0)));
+    /// assert!(!grid.is_alive((0, 0)));
+    /// ```
+    pub fn is_alive(&self, cell: Coords) -> bool {
+        self.contains(&cell)
+    }
+
+    /// Returns where `pattern`, moved to touch both axes, appears in the
+    /// grid, as the offset it's found at, topmost then leftmost first
+    ///
+    /// Every cell within the pattern's bounding box has to match, dead ones
+    /// included; cells around it don't matter. An empty pattern is found at
+    /// the origin.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::{Cell, Grid};
+    /// let grid = Grid::from(vec!["   ", "  #"]);
+    /// assert_eq!(grid.contains_pattern(&Grid::from(vec!["#"])), Some(Cell { x: 2, y: 1 }));
+    /// ```
+    pub fn contains_pattern(&self, pattern: &Grid) -> Option<Cell> {
+        let (pattern, _) = pattern.normalized();
+        let (width, height) = pattern.extent();
+        let anchor = match pattern.grid.iter().min_by_key(|&&(x, y)| (y, x)) {
+            Some(&anchor) => anchor,
+            None => return Some(Cell { x: 0, y: 0 }),
+        };
+        self.grid
+            .iter()
+            .filter(|&&(x, y)| x >= anchor.0 && y >= anchor.1)
+            .map(|&(x, y)| (x - anchor.0, y - anchor.1))
+            .filter(|&(ox, oy)| {
+                (0..width).all(|x| {
+                    (0..height).all(|y| {
+                        self.contains(&(ox + x, oy + y)) == pattern.contains(&(x, y))
+                    })
+                })
+            })
+            .min_by_key(|&(x, y)| (y, x))
+            .map(Cell::from)
+    }
+
+    /// Returns a `width` by `height` grid holding the pattern drawn in
+    /// `rows`, in the format `From<Vec<&str>>` reads
+    ///
+    /// Live cells that don't fit within the bounds are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from_str_bounded(&["#  ", "  #"], 2, 2);
+    /// assert_eq!(grid.population(), 1);
+    /// ```
+    pub fn from_str_bounded(rows: &[&str], width: usize, height: usize) -> Self {
+        Grid::parse(rows, Some((width, height)))
+    }
+
+    /// Returns an unbounded grid holding the pattern read from `reader`, one
+    /// row per line in the format `From<Vec<&str>>` reads
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from_reader(&b" #\n#"[..]).unwrap();
+    /// assert!(grid.is_alive((1, 0)));
+    /// assert!(grid.is_alive((0, 1)));
+    /// ```
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Grid> {
+        let lines = reader.lines().collect::<io::Result<Vec<String>>>()?;
+        let rows: Vec<&str> = lines.iter().map(String::as_str).collect();
+        Ok(Grid::parse(&rows, None))
+    }
+
+    /// Returns an unbounded grid where `rows[y]` lists the live runs of row
+    /// `y`, each as the `(x, length)` of its leftmost cell and length
+    ///
+    /// A run that would reach `usize::MAX` is cut short just before it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Two blocks side by side.
+    /// let grid = hemoglobin::Grid::from_runs(&[&[(0, 2), (3, 2)], &[(0, 2), (3, 2)]]);
+    /// assert_eq!(grid.population(), 8);
+    /// ```
+    pub fn from_runs(rows: &[&[(usize, usize)]]) -> Self {
+        let mut result = Grid::new(None);
+        for (y, runs) in rows.iter().enumerate() {
+            for &(start, length) in runs.iter() {
+                for x in start..start.saturating_add(length) {
+                    result.insert(&(x, y));
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns an unbounded grid spelling `text` in a 5x7 pixel font, with a
+    /// column between letters and a row between lines
+    ///
+    /// Letters are upper-cased first, and characters the font lacks come out
+    /// blank. It covers letters, digits and `.,:'!?-`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from_text("I");
+    /// assert!(grid.is_alive((2, 3)));
+    /// ```
+    pub fn from_text(text: &str) -> Self {
+        let mut result = Grid::new(None);
+        for (line, row) in text.lines().enumerate() {
+            for (i, c) in row.chars().enumerate() {
+                let glyph = glyph(c.to_ascii_uppercase());
+                for (dy, bits) in glyph.iter().enumerate() {
+                    for dx in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - dx)) != 0 {
+                            result.insert(&(
+                                i * (GLYPH_WIDTH + 1) + dx,
+                                line * (GLYPH_HEIGHT + 1) + dy,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn parse(rows: &[&str], bounds: Option<(usize, usize)>) -> Self {
+        let mut result = Grid::new(bounds);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    result.insert(&(x, y));
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the number of live cells
+    pub fn population(&self) -> usize {
+        self.grid.len()
+    }
+
+    /// Returns the number of live cell edges that face a dead cell or the
+    /// edge of the grid
+    pub fn perimeter(&self) -> usize {
+        let live = |x: Option<usize>, y: Option<usize>| match (x, y) {
+            (Some(x), Some(y)) => self.contains(&(x, y)),
+            _ => false,
+        };
+        self.grid
+            .iter()
+            .map(|&(x, y)| {
+                [
+                    live(x.checked_sub(1), Some(y)),
+                    live(Some(x + 1), Some(y)),
+                    live(Some(x), y.checked_sub(1)),
+                    live(Some(x), Some(y + 1)),
+                ]
+                .iter()
+                .filter(|&&alive| !alive)
+                .count()
+            })
+            .sum()
+    }
+
+    /// Splits the live cells into groups that touch, orthogonally or
+    /// diagonally, returning each group as a grid with the same bounds
+    ///
+    /// Components are ordered by their topmost, then leftmost, cell.
+    pub fn components(&self) -> Vec<Grid> {
+        let mut seen = CellSet::new();
+        let mut result = Vec::new();
+        for cell in self.iter_sorted() {
+            if !seen.insert(cell) {
+                continue;
             }
+            let mut component = Grid::new(self.bounds);
+            let mut pending = vec![cell];
+            while let Some(cell) = pending.pop() {
+                component.insert(&cell);
+                for neighbor in Cell::from(cell).neighbors().map(Coords::from) {
+                    if self.contains(&neighbor) && seen.insert(neighbor) {
+                        pending.push(neighbor);
+                    }
+                }
+            }
+            result.push(component);
+        }
+        result
+    }
+
+    fn capacity(&self) -> usize {
+        self.grid.capacity()
+    }
+
+    /// Releases memory held for more live cells than the grid has
+    pub fn shrink_to_fit(&mut self) {
+        self.grid.shrink_to_fit();
+    }
+
+    fn x_bound(&self) -> Option<usize> {
+        self.bounds.map(|(w, _)| w)
+    }
+
+    fn y_bound(&self) -> Option<usize> {
+        self.bounds.map(|(_, h)| h)
+    }
+
+    /// Replaces the grid's contents with random live cells
+    ///
+    /// Only bounded grids can be filled, unbounded ones give
+    /// `Error::Unbounded`.
+    #[cfg(feature = "random")]
+    pub fn gen(&mut self) -> Result<(), Error> {
+        self.gen_with_border(0)
+    }
+
+    /// Replaces the grid's contents with random live cells, leaving a dead
+    /// frame `border` cells thick around the edges
+    ///
+    /// Only bounded grids can be filled, unbounded ones give
+    /// `Error::Unbounded`.
+    #[cfg(feature = "random")]
+    pub fn gen_with_border(&mut self, border: usize) -> Result<(), Error> {
+        match self.bounds {
+            None => Err(Error::Unbounded),
             Some((w, h)) => {
-                if cell.0 <= w && cell.1 <= h {
-                    self.grid.insert(*cell);
+                self.grid.clear();
+                for x in border..w.saturating_sub(border) {
+                    for y in border..h.saturating_sub(border) {
+                        if rand::thread_rng().gen_bool(1.0/10.0) {
+                            self.insert(&(x, y));
+                        }
+                    }
                 }
+                Ok(())
             }
         }
     }
-    fn contains(&self, cell: &Cell) -> bool {
-        self.grid.contains(cell)
+
+    /// Replaces the grid's contents with random live cells scattered as
+    /// `mode` says, drawn reproducibly from `seed`
+    ///
+    /// Only bounded grids can be filled, unbounded ones give
+    /// `Error::Unbounded`.
+    #[cfg(feature = "random")]
+    pub fn gen_mode(&mut self, mode: SeedMode, seed: u64) -> Result<(), Error> {
+        let (w, h) = self.bounds.ok_or(Error::Unbounded)?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.grid.clear();
+        match mode {
+            SeedMode::Uniform => {
+                for x in 0..w {
+                    for y in 0..h {
+                        if rng.gen_bool(SPARSE_DENSITY) {
+                            self.insert(&(x, y));
+                        }
+                    }
+                }
+            }
+            SeedMode::Centered => {
+                for x in w / 4..w - w / 4 {
+                    for y in h / 4..h - h / 4 {
+                        if rng.gen_bool(DENSE_DENSITY) {
+                            self.insert(&(x, y));
+                        }
+                    }
+                }
+            }
+            SeedMode::Clustered { clusters, radius } => {
+                for (cx, cy) in cluster_centers(&mut rng, (w, h), clusters) {
+                    for x in cx.saturating_sub(radius)..w.min(cx + radius + 1) {
+                        for y in cy.saturating_sub(radius)..h.min(cy + radius + 1) {
+                            if within_radius((x, y), (cx, cy), radius)
+                                && rng.gen_bool(DENSE_DENSITY)
+                            {
+                                self.insert(&(x, y));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a random crossfade from `a` to `b`: as `t` goes from 0 to 1,
+    /// the chance of each cell being live moves from whether it's live in `a`
+    /// to whether it's live in `b`
+    ///
+    /// The result is bounded like both grids when they agree, big enough for
+    /// both when they're bounded differently and unbounded otherwise.
+    #[cfg(feature = "random")]
+    pub fn blend(a: &Grid, b: &Grid, t: f64, rng: &mut impl Rng) -> Grid {
+        let t = t.clamp(0.0, 1.0);
+        let bounds = match (a.bounds, b.bounds) {
+            (Some((wa, ha)), Some((wb, hb))) => Some((wa.max(wb), ha.max(hb))),
+            _ => None,
+        };
+        let mut result = Grid::new(bounds);
+        for &cell in a.grid.union(&b.grid) {
+            let chance = match (a.contains(&cell), b.contains(&cell)) {
+                (true, true) => 1.0,
+                (true, false) => 1.0 - t,
+                _ => t,
+            };
+            if rng.gen_bool(chance) {
+                result.insert(&cell);
+            }
+        }
+        result
+    }
+
+    /// Returns the corners of the smallest convex polygon around every live
+    /// cell, from the leftmost, then topmost, going clockwise as the grid is
+    /// drawn
+    ///
+    /// Cells along an edge that aren't corners are left out, so cells in a
+    /// line yield just its ends. With fewer than 3 live cells, all of them
+    /// are returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::{Cell, Grid};
+    /// let glider = Grid::from(vec![" # ", "  #", "###"]);
+    /// let hull = glider.convex_hull();
+    /// assert_eq!(hull[0], Cell { x: 0, y: 2 });
+    /// assert_eq!(hull.len(), 4);
+    /// ```
+    pub fn convex_hull(&self) -> Vec<Cell> {
+        let mut cells: Vec<Coords> = self.grid.iter().cloned().collect();
+        cells.sort_unstable();
+        if cells.len() < 3 {
+            return cells.into_iter().map(Cell::from).collect();
+        }
+        // Andrew's monotone chain: the upper edge as drawn from left to
+        // right, then the lower edge from right to left, each time dropping
+        // the last corner while it doesn't make a clockwise turn.
+        let turn = |o: Coords, a: Coords, b: Coords| {
+            let (o, a, b) = (
+                (o.0 as i64, o.1 as i64),
+                (a.0 as i64, a.1 as i64),
+                (b.0 as i64, b.1 as i64),
+            );
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+        let mut hull: Vec<Coords> = Vec::with_capacity(2 * cells.len());
+        for pass in 0..2 {
+            let start = hull.len();
+            let ordered: Box<dyn Iterator<Item = &Coords>> = if pass == 0 {
+                Box::new(cells.iter())
+            } else {
+                Box::new(cells.iter().rev())
+            };
+            for &cell in ordered {
+                while hull.len() >= start + 2
+                    && turn(hull[hull.len() - 2], hull[hull.len() - 1], cell) <= 0
+                {
+                    hull.pop();
+                }
+                hull.push(cell);
+            }
+            // The last corner of each edge starts the other one.
+            hull.pop();
+        }
+        hull.into_iter().map(Cell::from).collect()
+    }
+
+    /// Returns the size of the grid, or of the smallest grid anchored at the
+    /// origin holding every live cell if it's unbounded
+    fn extent(&self) -> (usize, usize) {
+        match self.bounds {
+            Some(bounds) => bounds,
+            None => (
+                self.grid.iter().map(|&(x, _)| x + 1).max().unwrap_or(0),
+                self.grid.iter().map(|&(_, y)| y + 1).max().unwrap_or(0),
+            ),
+        }
+    }
+
+    /// Returns the live cells translated so the pattern touches both axes, as
+    /// an unbounded grid, along with the translation that was undone
+    fn normalized(&self) -> (Grid, Coords) {
+        let min_x = self.grid.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = self.grid.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let mut result = Grid::new(None);
+        for &(x, y) in self.grid.iter() {
+            result.insert(&(x - min_x, y - min_y));
+        }
+        (result, (min_x, min_y))
+    }
+
+    /// Returns the rows of the grid top to bottom, each holding whether its
+    /// cells are live from left to right
+    ///
+    /// Unbounded grids are walked from the origin out to their furthest live
+    /// cells.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from_str_bounded(&["# ", " #"], 2, 2);
+    /// let rows: Vec<Vec<bool>> = grid.rows().collect();
+    /// assert_eq!(rows, vec![vec![true, false], vec![false, true]]);
+    /// ```
+    pub fn rows(&self) -> impl Iterator<Item = Vec<bool>> + '_ {
+        let (width, height) = self.extent();
+        (0..height).map(move |y| (0..width).map(|x| self.contains(&(x, y))).collect())
+    }
+
+    /// Returns a `width` by `height` grid holding the pattern, cropped to its
+    /// live cells and placed as `align` says
+    ///
+    /// Centering rounds towards the top left, and cells of a pattern too big
+    /// for the new grid are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::{Align, Grid};
+    /// let grid = Grid::from(vec!["#"]).padded(3, 3, Align::BottomRight);
+    /// assert!(grid.is_alive((2, 2)));
+    /// ```
+    pub fn padded(&self, width: usize, height: usize, align: Align) -> Grid {
+        let (pattern, _) = self.normalized();
+        let (w, h) = pattern.extent();
+        let (hx, hy) = align.halves();
+        let dx = width.saturating_sub(w) * hx / 2;
+        let dy = height.saturating_sub(h) * hy / 2;
+        let mut result = Grid::new(Some((width, height)));
+        for &(x, y) in pattern.grid.iter() {
+            result.insert(&(x + dx, y + dy));
+        }
+        result
+    }
+
+    /// Returns how many live cells of `pattern`, moved by `at`, land on live
+    /// cells of this grid, zero meaning it can be stamped without
+    /// overwriting anything
+    pub fn overlap_count(&self, pattern: &Grid, at: Cell) -> usize {
+        pattern
+            .grid
+            .iter()
+            .filter_map(|&(x, y)| Some((x.checked_add(at.x)?, y.checked_add(at.y)?)))
+            .filter(|cell| self.contains(cell))
+            .count()
+    }
+
+    /// Returns how many cells are live in one of the grids but not the other
+    pub fn hamming_distance(&self, other: &Grid) -> usize {
+        self.grid.symmetric_difference(&other.grid).count()
+    }
+
+    /// Returns a copy of the grid with `bounds` instead of its own, dropping
+    /// the live cells that fall outside them
+    pub fn with_bounds(&self, bounds: Option<(usize, usize)>) -> Grid {
+        let mut result = Grid::new(bounds);
+        for cell in self.grid.iter() {
+            result.insert(cell);
+        }
+        result
+    }
+
+    /// Returns the grid magnified `factor` times, each live cell becoming a
+    /// `factor` by `factor` block of live cells
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from(vec!["#", " #"]).scaled(2).unwrap();
+    /// assert_eq!(grid.population(), 8);
+    /// assert!(grid.is_alive((3, 3)));
+    /// ```
+    ///
+    /// Fails with `Error::TooLarge` if the magnified grid's bounds, or the
+    /// coordinates of its cells, wouldn't fit in a `usize`.
+    pub fn scaled(&self, factor: usize) -> Result<Grid, Error> {
+        let (width, height) = self.bounds.unwrap_or_else(|| self.extent());
+        match (width.checked_mul(factor), height.checked_mul(factor)) {
+            (Some(_), Some(_)) => {}
+            _ => {
+                return Err(Error::TooLarge {
+                    width: width.saturating_mul(factor),
+                    height: height.saturating_mul(factor),
+                    limit: usize::MAX,
+                })
+            }
+        }
+        let bounds = self.bounds.map(|(w, h)| (w * factor, h * factor));
+        let mut result = Grid::new(bounds);
+        for &(x, y) in self.grid.iter() {
+            for dy in 0..factor {
+                for dx in 0..factor {
+                    result.insert(&(x * factor + dx, y * factor + dy));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the live cells, top row first and left to right within a row
+    ///
+    /// Unlike iterating over the underlying set, the order doesn't depend on
+    /// how the grid was built.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = Coords> {
+        let mut cells: Vec<Coords> = self.grid.iter().cloned().collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.into_iter()
+    }
+
+    /// Returns each live cell with how many of its 8 neighbors are live, in
+    /// the order of `iter_sorted`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::{Cell, Grid};
+    /// let counts: Vec<(Cell, u8)> = Grid::from(vec!["##"]).iter_with_counts().collect();
+    /// assert_eq!(counts, vec![(Cell { x: 0, y: 0 }, 1), (Cell { x: 1, y: 0 }, 1)]);
+    /// ```
+    pub fn iter_with_counts(&self) -> impl Iterator<Item = (Cell, u8)> + '_ {
+        self.iter_sorted()
+            .map(move |cell| (Cell::from(cell), count_neighbors(self, &cell, DEAD_EDGES)))
+    }
+
+    /// Returns the smallest `(w, h)` such that moving the pattern, cropped to
+    /// its live cells, `w` cells right or `h` cells down lines it up with
+    /// itself wherever the two overlap, the tile that repeats it on a torus
+    ///
+    /// Each period has to be shorter than the pattern along its axis, so
+    /// `None` means the pattern doesn't repeat both ways.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let stripes = hemoglobin::Grid::from(vec!["# # #", "# # #"]);
+    /// assert_eq!(stripes.periodic_tile(), Some((2, 1)));
+    /// ```
+    pub fn periodic_tile(&self) -> Option<(usize, usize)> {
+        let (pattern, _) = self.normalized();
+        let (width, height) = pattern.extent();
+        // Every live cell must still be live a period ahead and behind,
+        // unless that takes it outside the pattern.
+        let repeats = |dx: usize, dy: usize| {
+            pattern.grid.iter().all(|&(x, y)| {
+                let ahead = x + dx >= width
+                    || y + dy >= height
+                    || pattern.contains(&(x + dx, y + dy));
+                let behind = x < dx || y < dy || pattern.contains(&(x - dx, y - dy));
+                ahead && behind
+            })
+        };
+        let w = (1..width).find(|&w| repeats(w, 0))?;
+        let h = (1..height).find(|&h| repeats(0, h))?;
+        Some((w, h))
+    }
+
+    /// Returns the rotations and reflections that leave the pattern, cropped
+    /// to its live cells, unchanged
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::{Grid, Symmetry};
+    /// let symmetries = Grid::from(vec!["###"]).symmetries();
+    /// assert!(symmetries.contains(Symmetry::Rotate180));
+    /// assert!(!symmetries.contains(Symmetry::Rotate90));
+    /// ```
+    pub fn symmetries(&self) -> SymmetrySet {
+        let orientations = self.orientations();
+        let mut result = SymmetrySet::default();
+        for &symmetry in Symmetry::ALL.iter() {
+            if orientations[symmetry as usize] == orientations[0] {
+                result.insert(symmetry);
+            }
+        }
+        result
+    }
+
+    /// Returns the grid's live cells, normalized, in each of the 8 ways the
+    /// square can be rotated and reflected onto itself
+    ///
+    /// The first is the pattern as it is, and the rest are in the order of
+    /// the `Symmetry` discriminants.
+    fn orientations(&self) -> Vec<Grid> {
+        let (pattern, _) = self.normalized();
+        let (width, height) = pattern.extent();
+        let last = (width.saturating_sub(1), height.saturating_sub(1));
+        let mut result = vec![pattern.clone()];
+        result.extend(Symmetry::ALL.iter().map(|&symmetry| {
+            let mut turned = Grid::new(None);
+            for &cell in pattern.grid.iter() {
+                turned.insert(&symmetry.apply(cell, last));
+            }
+            turned
+        }));
+        result
+    }
+
+    /// Returns the pattern in the extended Wechsler format used by apgcodes,
+    /// as laid out in the grid
+    ///
+    /// The pattern is cut into strips 5 rows tall, separated by `z`, and each
+    /// column of a strip is written as one base 32 digit with the top row as
+    /// the lowest bit. Runs of empty columns are shortened with `w`, `x` and
+    /// `y`, and dropped altogether at the end of a strip.
+    fn wechsler(&self) -> String {
+        let (pattern, _) = self.normalized();
+        let (width, height) = pattern.extent();
+        let mut strips = Vec::new();
+        for top in (0..height).step_by(5) {
+            let mut strip = String::new();
+            let mut zeros = 0;
+            for x in 0..width {
+                let column = (0..5)
+                    .filter(|&dy| pattern.contains(&(x, top + dy)))
+                    .fold(0, |acc, dy| acc | (1 << dy));
+                if column == 0 {
+                    zeros += 1;
+                    continue;
+                }
+                while zeros > 0 {
+                    match zeros {
+                        1 => strip.push('0'),
+                        2 => strip.push('w'),
+                        3 => strip.push('x'),
+                        n => {
+                            let run = n.min(39);
+                            strip.push('y');
+                            strip.push(WECHSLER_DIGITS[run - 4] as char);
+                            zeros -= run;
+                            continue;
+                        }
+                    }
+                    zeros = 0;
+                }
+                strip.push(WECHSLER_DIGITS[column] as char);
+            }
+            strips.push(strip);
+        }
+        strips.join("z")
+    }
+
+    /// Returns the apgcode naming the pattern under `rule`, such as `xs4_33`
+    /// for the block, or `None` if the grid is empty or the pattern doesn't
+    /// repeat within `APGCODE_MAX_PERIOD` generations, or is too large to
+    /// simulate
+    ///
+    /// Still lifes get the `xs` prefix followed by their population,
+    /// oscillators `xp` and spaceships `xq`, followed by their period. The
+    /// part after the underscore is the shortest, then alphabetically first,
+    /// `wechsler` encoding over every phase and orientation.
+    pub fn to_apgcode(&self, rule: &Rule) -> Option<String> {
+        if self.grid.is_empty() {
+            return None;
+        }
+        let mut world: World = World::minimal(self, rule.clone(), APGCODE_MAX_PERIOD + 1).ok()?;
+        let (start, origin) = world.grid.normalized();
+        let mut phases = vec![start.clone()];
+        for period in 1..=APGCODE_MAX_PERIOD {
+            world.step();
+            let (current, offset) = world.grid.normalized();
+            if current != start {
+                phases.push(current);
+                continue;
+            }
+            let prefix = if offset != origin {
+                format!("xq{}", period)
+            } else if period == 1 {
+                format!("xs{}", start.population())
+            } else {
+                format!("xp{}", period)
+            };
+            let code = phases
+                .iter()
+                .flat_map(|phase| phase.orientations())
+                .map(|grid| grid.wechsler())
+                .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+                .expect("there's at least one phase");
+            return Some(format!("{}_{}", prefix, code));
+        }
+        None
+    }
+
+    /// Returns whether the grid is a garden of Eden under `rule`: no grid of
+    /// the same bounds becomes this one after a step with dead edges
+    ///
+    /// Every possible predecessor is tried, so this fails with
+    /// `Error::TooLarge` for grids of more than `GARDEN_OF_EDEN_MAX_CELLS`
+    /// cells, and with `Error::Unbounded` for unbounded ones.
+    pub fn is_garden_of_eden(&self, rule: &Rule) -> Result<bool, Error> {
+        let (width, height) = self.bounds.ok_or(Error::Unbounded)?;
+        let cells = match width.checked_mul(height) {
+            Some(cells) if cells <= GARDEN_OF_EDEN_MAX_CELLS => cells,
+            _ => {
+                return Err(Error::TooLarge {
+                    width,
+                    height,
+                    limit: GARDEN_OF_EDEN_MAX_CELLS,
+                })
+            }
+        };
+        let mut world: World = World::new(width, height, rule.clone());
+        for candidate in 0..1usize << cells {
+            world.grid.grid.clear();
+            for i in (0..cells).filter(|i| candidate >> i & 1 == 1) {
+                world.grid.insert(&(i % width, i / width));
+            }
+            if world.next_grid() == *self {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Writes the grid to `path` as an ASCII (`P1`) portable bitmap, with
+    /// live cells black
+    ///
+    /// Unbounded grids are written from the origin out to their furthest live
+    /// cells.
+    pub fn to_pbm(&self, path: &Path) -> Result<(), Error> {
+        let (width, height) = self.extent();
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "P1")?;
+        writeln!(file, "{} {}", width, height)?;
+        for y in 0..height {
+            let row: Vec<&str> = (0..width)
+                .map(|x| if self.contains(&(x, y)) { "1" } else { "0" })
+                .collect();
+            writeln!(file, "{}", row.join(" "))?;
+        }
+        Ok(file.flush()?)
+    }
+
+    /// Returns the width and height in pixels of the grid drawn with every
+    /// cell as a square of `cell_px` pixels, or `Error::TooLarge` if either
+    /// doesn't fit in a `u32` or there would be more than `MAX_PIXELS` pixels
+    fn raster_size(&self, cell_px: u32) -> Result<(u32, u32), Error> {
+        let (width, height) = self.extent();
+        let to_px = |cells: usize| {
+            u32::try_from(cells)
+                .ok()
+                .and_then(|c| c.checked_mul(cell_px))
+        };
+        match (to_px(width), to_px(height)) {
+            (Some(w), Some(h)) if u64::from(w) * u64::from(h) <= MAX_PIXELS as u64 => Ok((w, h)),
+            (w, h) => Err(Error::TooLarge {
+                width: w.map_or(usize::MAX, |w| w as usize),
+                height: h.map_or(usize::MAX, |h| h as usize),
+                limit: MAX_PIXELS,
+            }),
+        }
+    }
+
+    /// Returns the grid drawn with every cell as a square of `cell_px` pixels,
+    /// one byte per pixel, 1 for live cells and 0 for dead ones, row by row
+    ///
+    /// Fails as `raster_size` does.
+    fn rasterize(&self, cell_px: u32) -> Result<Vec<u8>, Error> {
+        let (width_px, height_px) = self.raster_size(cell_px)?;
+        let (width_px, height_px) = (width_px as usize, height_px as usize);
+        let cell_px = cell_px as usize;
+        let mut pixels = Vec::with_capacity(width_px * height_px);
+        for y in 0..height_px {
+            for x in 0..width_px {
+                pixels.push(self.contains(&(x / cell_px, y / cell_px)) as u8);
+            }
+        }
+        Ok(pixels)
+    }
+
+    /// Returns the grid in the Life 1.06 format: a `#Life 1.06` header
+    /// followed by one `x y` line per live cell, top row first
+    pub fn to_life106(&self) -> String {
+        let mut result = String::from("#Life 1.06\n");
+        for (x, y) in self.iter_sorted() {
+            result.push_str(&format!("{} {}\n", x, y));
+        }
+        result
+    }
+
+    /// Writes the pattern in the run length encoded (RLE) format, cropped to
+    /// its live cells, with `rule` in the header if given
+    ///
+    /// The output is produced a line at a time, wrapped at 70 characters, so
+    /// it's never held in memory all at once.
+    pub fn write_rle(&self, writer: &mut impl Write, rule: Option<&Rule>) -> io::Result<()> {
+        let (pattern, _) = self.normalized();
+        let (width, height) = pattern.extent();
+        match rule {
+            Some(rule) => writeln!(writer, "x = {}, y = {}, rule = {}", width, height, rule)?,
+            None => writeln!(writer, "x = {}, y = {}", width, height)?,
+        }
+
+        let mut rows: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (x, y) in pattern.iter_sorted() {
+            match rows.last_mut() {
+                Some((row, xs)) if *row == y => xs.push(x),
+                _ => rows.push((y, vec![x])),
+            }
+        }
+        let mut line_len = 0;
+        let mut push = |count: usize, tag: char| -> io::Result<()> {
+            let token = if count == 1 {
+                tag.to_string()
+            } else {
+                format!("{}{}", count, tag)
+            };
+            if line_len + token.len() > RLE_LINE_LEN {
+                writeln!(writer)?;
+                line_len = 0;
+            }
+            line_len += token.len();
+            write!(writer, "{}", token)
+        };
+        let mut last_y = 0;
+        for (y, xs) in rows {
+            if y > last_y {
+                push(y - last_y, '$')?;
+            }
+            let mut next_x = 0;
+            let mut run = 0;
+            for x in xs {
+                if x != next_x {
+                    if run > 0 {
+                        push(run, 'o')?;
+                    }
+                    push(x - next_x, 'b')?;
+                    run = 0;
+                }
+                run += 1;
+                next_x = x + 1;
+            }
+            push(run, 'o')?;
+            last_y = y;
+        }
+        push(1, '!')?;
+        writeln!(writer)
+    }
+
+    /// Returns the pattern in the RLE format `write_rle` writes
+    pub fn to_rle(&self, rule: Option<&Rule>) -> String {
+        let mut result = Vec::new();
+        self.write_rle(&mut result, rule).expect("writing to a Vec can't fail");
+        String::from_utf8(result).expect("RLE output is ASCII")
+    }
+
+    /// Parses a pattern in the RLE format into an unbounded grid
+    ///
+    /// Only two-state patterns are understood, and the header's size and
+    /// rule are ignored. Fails if the header is missing, the pattern holds
+    /// anything but runs of `b`, `o` and `$` or it reaches past the largest
+    /// coordinates a grid can hold, and with `Error::TooLarge` if the box
+    /// from the origin to its live cells holds more than `MAX_CELLS` cells.
+    pub fn from_rle(s: &str) -> Result<Grid, Error> {
+        let mut lines = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        match lines.next() {
+            Some(header) if header.starts_with('x') => {}
+            _ => return Err(Error::Parse("missing RLE header".to_string())),
+        }
+        let mut result = Grid::new(None);
+        let (mut x, mut y) = (0usize, 0usize);
+        let mut width = 0;
+        let mut count: Option<usize> = None;
+        let too_far = || Error::Parse("RLE pattern runs past the largest grid".to_string());
+        for c in lines.flat_map(str::chars) {
+            if let Some(digit) = c.to_digit(10) {
+                let run = count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|run| run.checked_add(digit as usize))
+                    .ok_or_else(too_far)?;
+                count = Some(run);
+                continue;
+            }
+            let n = count.take().unwrap_or(1);
+            match c {
+                'b' => x = x.checked_add(n).ok_or_else(too_far)?,
+                'o' => {
+                    let end = x.checked_add(n).ok_or_else(too_far)?;
+                    width = end.max(width);
+                    let height = y.checked_add(1).ok_or_else(too_far)?;
+                    if width.checked_mul(height).filter(|&cells| cells <= MAX_CELLS).is_none() {
+                        return Err(Error::TooLarge {
+                            width,
+                            height,
+                            limit: MAX_CELLS,
+                        });
+                    }
+                    for x in x..end {
+                        result.insert(&(x, y));
+                    }
+                    x = end;
+                }
+                '$' => {
+                    x = 0;
+                    y = y.checked_add(n).ok_or_else(too_far)?;
+                }
+                '!' => return Ok(result),
+                c if c.is_whitespace() => {}
+                c => return Err(Error::Parse(format!("unexpected {:?} in RLE pattern", c))),
+            }
+        }
+        Err(Error::Parse("RLE pattern isn't terminated by !".to_string()))
+    }
+
+    /// Parses a pattern in the Life 1.06 format into an unbounded grid
+    ///
+    /// Life 1.06 coordinates may be negative, so the pattern is translated to
+    /// put its leftmost and topmost cells on the axes. Fails if the header is
+    /// missing, a line isn't an `x y` pair or the cells lie too far apart for
+    /// a grid's coordinates.
+    pub fn from_life106(s: &str) -> Result<Grid, Error> {
+        let mut lines = s.lines();
+        if lines.next().map(str::trim) != Some("#Life 1.06") {
+            return Err(Error::Parse("missing #Life 1.06 header".to_string()));
+        }
+        let mut cells = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut coords = line.split_whitespace().map(|c| c.parse::<i64>());
+            match (coords.next(), coords.next(), coords.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => cells.push((x, y)),
+                _ => return Err(Error::Parse(format!("not a coordinate pair: {}", line))),
+            }
+        }
+        let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let offset = |c: i64, min: i64| {
+            c.checked_sub(min)
+                .and_then(|c| usize::try_from(c).ok())
+                .ok_or_else(|| Error::Parse("pattern is too wide to fit in a grid".to_string()))
+        };
+        let mut result = Grid::new(None);
+        for (x, y) in cells {
+            result.insert(&(offset(x, min_x)?, offset(y, min_y)?));
+        }
+        Ok(result)
+    }
+}
+
+impl From<Vec<&str>> for Grid {
+    /// Returns a Grid interpreted from a string representation
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - Representation of the grid. Each element of the vector
+    ///   represents a row in the grid. Hash marks # indicate live cells.
+    ///   For example vec!["#  ", "   ", " # "] represents a grid with live
+    ///   cells at (0, 0) and (2, 1).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let grid = hemoglobin::Grid::from(vec!["#  ", "   ", " # "]);
+    /// ```
+    fn from(s: Vec<&str>) -> Self {
+        Grid::parse(&s, None)
+    }
+}
+
+/// What a pattern file says about the pattern it holds
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PatternMeta {
+    /// The pattern's name
+    pub name: Option<String>,
+    /// Who found the pattern or wrote the file
+    pub author: Option<String>,
+    /// Free-form comment lines, in the order they appear
+    pub comments: Vec<String>,
+}
+
+/// A pattern loaded from a file, along with the file's rule and metadata
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pattern {
+    /// The live cells, as an unbounded grid
+    pub grid: Grid,
+    /// The rule named in the file, if it names one
+    pub rule: Option<Rule>,
+    /// Name, author and comments from the file
+    pub meta: PatternMeta,
+}
+
+impl Pattern {
+    /// Parses a pattern in the RLE format, keeping its `#N` name, `#O`
+    /// author and `#C` comment lines
+    ///
+    /// The header's rule may be a decimal rule number or in B/S notation.
+    /// Fails on the same input `Grid::from_rle` does, or on an unknown rule.
+    pub fn from_rle(s: &str) -> Result<Pattern, Error> {
+        let grid = Grid::from_rle(s)?;
+        let mut meta = PatternMeta::default();
+        let mut rule = None;
+        for line in s.lines().map(str::trim) {
+            let text = |tag| line.strip_prefix(tag).map(|text: &str| text.trim().to_string());
+            if let Some(name) = text("#N") {
+                meta.name = Some(name);
+            } else if let Some(author) = text("#O") {
+                meta.author = Some(author);
+            } else if let Some(comment) = text("#C").or_else(|| text("#c")) {
+                meta.comments.push(comment);
+            } else if line.starts_with('x') {
+                for field in line.split(',') {
+                    let mut kv = field.splitn(2, '=').map(str::trim);
+                    if let (Some("rule"), Some(value)) = (kv.next(), kv.next()) {
+                        rule = Some(value.parse().or_else(|_| Rule::from_bs(value))?);
+                    }
+                }
+                break;
+            }
+        }
+        Ok(Pattern { grid, rule, meta })
+    }
+
+    /// Parses a pattern in the plaintext `.cells` format, where `O` marks a
+    /// live cell and `.` a dead one, keeping its `!Name:` and `!Author:`
+    /// lines and any other `!` lines as comments
+    ///
+    /// The format names no rule. Fails on any other character in the rows.
+    pub fn from_cells(s: &str) -> Result<Pattern, Error> {
+        let mut grid = Grid::new(None);
+        let mut meta = PatternMeta::default();
+        let mut y = 0;
+        for line in s.lines() {
+            if let Some(text) = line.strip_prefix('!') {
+                let text = text.trim();
+                if let Some(name) = text.strip_prefix("Name:") {
+                    meta.name = Some(name.trim().to_string());
+                } else if let Some(author) = text.strip_prefix("Author:") {
+                    meta.author = Some(author.trim().to_string());
+                } else {
+                    meta.comments.push(text.to_string());
+                }
+                continue;
+            }
+            for (x, c) in line.trim_end().chars().enumerate() {
+                match c {
+                    'O' => grid.insert(&(x, y)),
+                    '.' => {}
+                    c => return Err(Error::Parse(format!("unexpected {:?} in cells pattern", c))),
+                }
+            }
+            y += 1;
+        }
+        Ok(Pattern {
+            grid,
+            rule: None,
+            meta,
+        })
+    }
+}
+
+/// A rotation or reflection of a pattern's bounding box onto itself
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Symmetry {
+    /// Mirrors left to right
+    FlipHorizontal = 1,
+    /// Mirrors top to bottom
+    FlipVertical = 2,
+    /// Turns half way around
+    Rotate180 = 3,
+    /// Mirrors along the diagonal from the top left corner
+    FlipDiagonal = 4,
+    /// Turns a quarter clockwise
+    Rotate90 = 5,
+    /// Turns a quarter counterclockwise
+    Rotate270 = 6,
+    /// Mirrors along the diagonal from the top right corner
+    FlipAntiDiagonal = 7,
+}
+
+impl Symmetry {
+    /// Every symmetry, in order
+    pub const ALL: [Symmetry; 7] = [
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::Rotate180,
+        Symmetry::FlipDiagonal,
+        Symmetry::Rotate90,
+        Symmetry::Rotate270,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    /// Returns where `cell` of a box whose bottom right cell is `(w, h)`
+    /// lands when the box is turned by the symmetry
+    fn apply(self, (x, y): Coords, (w, h): Coords) -> Coords {
+        match self {
+            Symmetry::FlipHorizontal => (w - x, y),
+            Symmetry::FlipVertical => (x, h - y),
+            Symmetry::Rotate180 => (w - x, h - y),
+            Symmetry::FlipDiagonal => (y, x),
+            Symmetry::Rotate90 => (h - y, x),
+            Symmetry::Rotate270 => (y, w - x),
+            Symmetry::FlipAntiDiagonal => (h - y, w - x),
+        }
+    }
+}
+
+/// The symmetries a pattern has, as found by `Grid::symmetries`
+///
+/// The identity is left out, so an asymmetric pattern has an empty set and
+/// one with every symmetry of the square (the group D4) has all 7.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct SymmetrySet {
+    bits: u8,
+}
+
+impl SymmetrySet {
+    fn insert(&mut self, symmetry: Symmetry) {
+        self.bits |= 1 << symmetry as u8;
+    }
+
+    /// Returns whether the pattern has `symmetry`
+    pub fn contains(&self, symmetry: Symmetry) -> bool {
+        self.bits & (1 << symmetry as u8) != 0
+    }
+
+    /// Returns how many symmetries the pattern has, besides the identity
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Returns whether the pattern has no symmetry besides the identity
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns the symmetries in the set, in the order of `Symmetry::ALL`
+    pub fn iter(&self) -> impl Iterator<Item = Symmetry> + '_ {
+        Symmetry::ALL.iter().cloned().filter(move |&s| self.contains(s))
+    }
+}
+
+/// Where `Grid::padded` places a pattern within the new grid
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Align {
+    /// Returns how many halves of the free space go before the pattern
+    /// horizontally and vertically
+    fn halves(self) -> (usize, usize) {
+        match self {
+            Align::TopLeft => (0, 0),
+            Align::Top => (1, 0),
+            Align::TopRight => (2, 0),
+            Align::Left => (0, 1),
+            Align::Center => (1, 1),
+            Align::Right => (2, 1),
+            Align::BottomLeft => (0, 2),
+            Align::Bottom => (1, 2),
+            Align::BottomRight => (2, 2),
+        }
+    }
+}
+
+/// What `World::load` does with live cells that lie outside the world
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverflowPolicy {
+    /// Drop the cells that don't fit
+    Clip,
+    /// Fail with `Error::OutOfBounds`, leaving the world as it was
+    Error,
+    /// Grow the world right and down until every live cell fits, whatever
+    /// the pattern's own bounds
+    Resize,
+}
+
+/// How `Grid::gen_mode` scatters live cells
+#[cfg(feature = "random")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SeedMode {
+    /// Sparse live cells anywhere in the grid, like `Grid::gen`
+    Uniform,
+    /// Dense live cells in the middle half of the grid along each axis
+    Centered,
+    /// Dense discs of live cells of `radius` around `clusters` random centers
+    Clustered { clusters: usize, radius: usize },
+}
+
+/// Most cells `Grid::is_garden_of_eden` searches the predecessors of
+pub const GARDEN_OF_EDEN_MAX_CELLS: usize = 16;
+
+/// Longest period `Grid::to_apgcode` looks for
+pub const APGCODE_MAX_PERIOD: usize = 64;
+
+/// Digits of the extended Wechsler format: columns use the first 32, runs of
+/// empty columns after a `y` all 36
+const WECHSLER_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Longest line `Grid::write_rle` writes
+const RLE_LINE_LEN: usize = 70;
+
+/// Version tag at the start of every `Rule::to_url_fragment` string
+pub const URL_FRAGMENT_TAG: &str = "h1.";
+
+/// Digits of URL-safe base64
+const BASE64URL_DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Width of a letter drawn by `Grid::from_text`
+const GLYPH_WIDTH: usize = 5;
+
+/// Height of a letter drawn by `Grid::from_text`
+const GLYPH_HEIGHT: usize = 7;
+
+/// Chance of a cell being live when seeding sparsely
+#[cfg(feature = "random")]
+const SPARSE_DENSITY: f64 = 0.1;
+
+/// Chance of a cell being live inside a densely seeded region
+#[cfg(feature = "random")]
+const DENSE_DENSITY: f64 = 0.5;
+
+/// Returns `clusters` random cells of a grid of `bounds`, or none if the
+/// grid has no cells
+#[cfg(feature = "random")]
+fn cluster_centers(rng: &mut impl Rng, bounds: (usize, usize), clusters: usize) -> Vec<Coords> {
+    let (w, h) = bounds;
+    if w == 0 || h == 0 {
+        return Vec::new();
+    }
+    (0..clusters)
+        .map(|_| (rng.gen_range(0, w), rng.gen_range(0, h)))
+        .collect()
+}
+
+/// Returns whether `a` lies within `radius` of `b`
+#[cfg(feature = "random")]
+fn within_radius(a: Coords, b: Coords, radius: usize) -> bool {
+    let dx = a.0.max(b.0) - a.0.min(b.0);
+    let dy = a.1.max(b.1) - a.1.min(b.1);
+    dx * dx + dy * dy <= radius * radius
+}
+
+/// Selects which of the 9 cells of a 3x3 neighborhood a rule looks at
+///
+/// Entry `dx + 3 * dy` stands for the cell at offset `(dx - 1, dy - 1)` from
+/// the center, so entry 4 is the center itself.
+pub type NeighborhoodMask = [bool; 9];
+
+/// The full 3x3 neighborhood
+pub const MOORE: NeighborhoodMask = [true; 9];
+
+/// The center and its four orthogonal neighbors
+pub const VON_NEUMANN: NeighborhoodMask =
+    [false, true, false, true, true, true, false, true, false];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rule {
+    dec: BigUint,
+    bin: BitVec,
+    mask: NeighborhoodMask,
+}
+
+impl From<BigUint> for Rule {
+    fn from(x: BigUint) -> Self {
+        Rule::with_mask(x, MOORE)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.dec)
+    }
+}
+
+impl From<String> for Rule {
+    /// Returns the rule numbered `s` in decimal
+    ///
+    /// # Panics
+    ///
+    /// Panics if `s` isn't a valid rule number; use `str::parse` to handle
+    /// that case.
+    fn from(s: String) -> Self {
+        s.parse().unwrap()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    /// Parses a rule number, written in decimal, over the full neighborhood
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let x = s
+            .trim()
+            .parse::<BigUint>()
+            .map_err(|e| Error::Parse(format!("invalid rule number: {}", e)))?;
+        if x.bits() > 512 {
+            return Err(Error::Parse("rule numbers must be below 2^512".to_string()));
+        }
+        Ok(Rule::from(x))
+    }
+}
+
+impl Rule {
+    /// Returns the rule numbered `x` over the neighbors selected by `mask`
+    ///
+    /// The rule's table has one entry per state of the selected neighbors, so
+    /// `x` ranges from 0 to 2^(2^n) - 1 where n is the number of entries set
+    /// in `mask`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is past that range; `try_with_mask` fails instead.
+    pub fn with_mask(x: BigUint, mask: NeighborhoodMask) -> Self {
+        Rule::try_with_mask(x, mask).expect("rule number too large for its neighborhood")
+    }
+
+    /// Returns the rule numbered `x` over the neighbors selected by `mask`,
+    /// like `with_mask`, or fails if `x` is too large for them
+    pub fn try_with_mask(x: BigUint, mask: NeighborhoodMask) -> Result<Self, Error> {
+        let states = 1usize << mask.iter().filter(|&&m| m).count();
+        if x.bits() > states {
+            return Err(Error::Parse(
+                "rule number too large for its neighborhood".to_string(),
+            ));
+        }
+        let bytes = x.to_bytes_le();
+        let mut bin = BitVec::from_elem(states, false);
+        for state in 0..states.min(8 * bytes.len()) {
+            bin.set(state, (bytes[state / 8] >> (state % 8)) & 1 == 1);
+        }
+        Ok(Rule { dec: x, bin, mask })
+    }
+
+    /// Returns a rule over the full neighborhood whose next states are drawn
+    /// from `rng`, each equally likely to be live or dead
+    #[cfg(feature = "random")]
+    pub fn random(rng: &mut impl Rng) -> Rule {
+        let mut bin = BitVec::from_elem(512, false);
+        for state in 0..bin.len() {
+            bin.set(state, rng.gen());
+        }
+        Rule::from_bits(bin, MOORE)
+    }
+
+    /// Returns a copy of the rule with the output for each neighborhood state
+    /// flipped with probability `rate`, drawn from `rng`
+    #[cfg(feature = "random")]
+    pub fn mutate(&self, rate: f64, rng: &mut impl Rng) -> Rule {
+        let mut bin = self.bin.clone();
+        for state in 0..bin.len() {
+            if rng.gen_bool(rate) {
+                bin.set(state, !bin[state]);
+            }
+        }
+        Rule::from_bits(bin, self.mask)
+    }
+
+    /// Returns a rule taking the output for each neighborhood state from `a`
+    /// or `b`, picked at random from `rng`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` look at different neighborhoods.
+    #[cfg(feature = "random")]
+    pub fn crossover(a: &Rule, b: &Rule, rng: &mut impl Rng) -> Rule {
+        assert_eq!(a.mask, b.mask, "can't cross rules over different neighborhoods");
+        let mut bin = a.bin.clone();
+        for state in 0..bin.len() {
+            if rng.gen() {
+                bin.set(state, b.bin[state]);
+            }
+        }
+        Rule::from_bits(bin, a.mask)
+    }
+
+    /// Returns the outer-totalistic rule written in B/S notation, such as
+    /// `B3/S23` for Conway's Game of Life
+    ///
+    /// The digits after `B` are the neighbor counts that bring a dead cell to
+    /// life and those after `S` the counts that keep a live cell alive.
+    /// Fails if `s` isn't in that form.
+    pub fn from_bs(s: &str) -> Result<Rule, Error> {
+        OuterTotalisticRule::from_bs(s).map(|rule| Rule::from(&rule))
+    }
+
+    /// Returns the well-known rule called `name`, ignoring case
+    ///
+    /// Known rules are Conway, HighLife, Seeds, Day & Night and Replicator.
+    pub fn named(name: &str) -> Option<Rule> {
+        NAMED_RULES
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .and_then(|(_, bs)| Rule::from_bs(bs).ok())
+    }
+
+    /// Returns the rule as a short string that can go in a URL unescaped,
+    /// which `from_url_fragment` reads back
+    ///
+    /// The string is `URL_FRAGMENT_TAG` followed by the mask, one bit per
+    /// cell in two bytes, and the rule number in little-endian bytes, in
+    /// URL-safe base64 without padding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let conway = hemoglobin::Rule::named("Conway").unwrap();
+    /// let fragment = conway.to_url_fragment();
+    /// assert_eq!(hemoglobin::Rule::from_url_fragment(&fragment).unwrap(), conway);
+    /// ```
+    pub fn to_url_fragment(&self) -> String {
+        let mask = (0..9)
+            .filter(|&i| self.mask[i])
+            .fold(0u16, |mask, i| mask | 1 << i);
+        let mut bytes = mask.to_le_bytes().to_vec();
+        let mut number = self.dec.to_bytes_le();
+        number.resize(self.bin.len().div_ceil(8), 0);
+        bytes.extend(number);
+        format!("{}{}", URL_FRAGMENT_TAG, encode_base64url(&bytes))
+    }
+
+    /// Returns the rule written by `to_url_fragment`, failing if `s` isn't
+    /// such a string
+    pub fn from_url_fragment(s: &str) -> Result<Rule, Error> {
+        let invalid = || Error::Parse(format!("not a rule fragment: {}", s));
+        let bytes = s
+            .strip_prefix(URL_FRAGMENT_TAG)
+            .and_then(decode_base64url)
+            .ok_or_else(invalid)?;
+        if bytes.len() < 2 {
+            return Err(invalid());
+        }
+        let mask_bits = u16::from_le_bytes([bytes[0], bytes[1]]);
+        if mask_bits >> 9 != 0 {
+            return Err(invalid());
+        }
+        let mut mask = [false; 9];
+        for (i, m) in mask.iter_mut().enumerate() {
+            *m = mask_bits & 1 << i != 0;
+        }
+        let states: usize = 1 << mask_bits.count_ones();
+        let number = &bytes[2..];
+        if number.len() != states.div_ceil(8) {
+            return Err(invalid());
+        }
+        let mut bin = BitVec::from_elem(states, false);
+        for (i, byte) in number.iter().enumerate() {
+            for bit in 0..8 {
+                if byte & 1 << bit != 0 {
+                    if i * 8 + bit >= states {
+                        return Err(invalid());
+                    }
+                    bin.set(i * 8 + bit, true);
+                }
+            }
+        }
+        Ok(Rule::from_bits(bin, mask))
+    }
+
+    /// Returns the rule in B/S notation if it's one of the rules `named`
+    /// knows
+    fn known_bs(&self) -> Option<&'static str> {
+        NAMED_RULES
+            .iter()
+            .map(|&(_, bs)| bs)
+            .find(|bs| Rule::from_bs(bs).ok().as_ref() == Some(self))
+    }
+
+    /// Builds a rule from its table, where bit `i` is the next state of a
+    /// cell whose neighborhood is in state `i`
+    fn from_bits(bin: BitVec, mask: NeighborhoodMask) -> Self {
+        let mut bytes = vec![0u8; bin.len().div_ceil(8)];
+        for (i, bit) in bin.iter().enumerate() {
+            if bit {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        Rule {
+            dec: BigUint::from_bytes_le(&bytes),
+            bin,
+            mask,
+        }
+    }
+
+    /// Returns a copy of the rule with the output for `state` inverted
+    fn flipped(&self, state: usize) -> Rule {
+        let mut bin = self.bin.clone();
+        bin.set(state, !bin[state]);
+        Rule::from_bits(bin, self.mask)
+    }
+
+    /// Sets whether a dead cell with `count` live neighbors comes to life,
+    /// like adding or removing a digit after the `B` of B/S notation
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule doesn't look at the full Moore neighborhood.
+    pub fn set_birth(&mut self, count: u8, on: bool) {
+        self.set_outer_totalistic(false, count, on);
+    }
+
+    /// Sets whether a live cell with `count` live neighbors stays alive, like
+    /// adding or removing a digit after the `S` of B/S notation
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule doesn't look at the full Moore neighborhood.
+    pub fn set_survival(&mut self, count: u8, on: bool) {
+        self.set_outer_totalistic(true, count, on);
+    }
+
+    /// Sets the output for every state with the given center and number of
+    /// live neighbors, and renumbers the rule to match
+    fn set_outer_totalistic(&mut self, center: bool, count: u8, on: bool) {
+        assert_eq!(self.mask, MOORE, "B/S edits need a Moore neighborhood rule");
+        let mut bin = self.bin.clone();
+        for state in 0..bin.len() {
+            let neighbors = (state & !(1 << 4)).count_ones() as u8;
+            if (state & (1 << 4) != 0) == center && neighbors == count {
+                bin.set(state, on);
+            }
+        }
+        *self = Rule::from_bits(bin, self.mask);
+    }
+
+    /// Returns the rule numbered lowest among this one and those it turns
+    /// into when every neighborhood is rotated or reflected, so rules that
+    /// behave the same up to symmetry share it
+    ///
+    /// Only the symmetries that map the neighbors the rule looks at onto
+    /// themselves are tried.
+    pub fn canonical_rule(&self) -> Rule {
+        Symmetry::ALL
+            .iter()
+            .filter_map(|&symmetry| self.turned(symmetry))
+            .chain(std::iter::once(self.clone()))
+            .min_by(|a, b| a.dec.cmp(&b.dec))
+            .expect("the rule itself is always a candidate")
+    }
+
+    /// Returns the rule that does to patterns turned by `symmetry` what this
+    /// one does to the originals, or `None` if `symmetry` doesn't map the
+    /// mask onto itself
+    fn turned(&self, symmetry: Symmetry) -> Option<Rule> {
+        let moved = |i: usize| {
+            let (x, y) = symmetry.apply((i % 3, i / 3), (2, 2));
+            x + 3 * y
+        };
+        if (0..9).any(|i| self.mask[i] != self.mask[moved(i)]) {
+            return None;
+        }
+        let inputs: Vec<usize> = (0..9).filter(|&i| self.mask[i]).collect();
+        // Bit `rank` of a masked state moves to the rank of its cell's new
+        // position.
+        let targets: Vec<usize> = inputs
+            .iter()
+            .map(|&i| inputs.iter().position(|&j| j == moved(i)).unwrap())
+            .collect();
+        let mut bin = BitVec::from_elem(self.bin.len(), false);
+        for state in 0..self.bin.len() {
+            let turned = targets
+                .iter()
+                .enumerate()
+                .filter(|&(rank, _)| state >> rank & 1 == 1)
+                .fold(0, |turned, (_, &target)| turned | 1 << target);
+            bin.set(turned, self.bin[state]);
+        }
+        Some(Rule::from_bits(bin, self.mask))
+    }
+
+    /// Returns the next state of a cell whose neighborhood is in `state`, as
+    /// numbered by `state_at`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state` is past the end of the rule's table, which has 2^n
+    /// entries for a neighborhood of n cells.
+    pub fn output_for(&self, state: usize) -> bool {
+        self.bin[state]
+    }
+
+    /// Returns the next states of the central 2x2 cells of a 4x4 block laid
+    /// out as `World::block_at` returns it, with bit `x + 2 * y` for the
+    /// cell at `(x + 1, y + 1)`
+    fn block_outcome(&self, block: u16) -> u8 {
+        let mut outcome = 0;
+        for i in 0..4 {
+            let (ox, oy) = (i % 2, i / 2);
+            let mut state = 0;
+            let mut bit = 0;
+            for position in 0..9 {
+                if !self.mask[position] {
+                    continue;
+                }
+                let (dx, dy) = (position % 3, position / 3);
+                if block & (1 << ((ox + dx) + 4 * (oy + dy))) != 0 {
+                    state |= 1 << bit;
+                }
+                bit += 1;
+            }
+            if self.bin[state] {
+                outcome |= 1 << i;
+            }
+        }
+        outcome
+    }
+
+    /// Returns the number of neighborhood states that make the center cell
+    /// live, a cheap way to bucket rules before simulating them
+    pub fn popcount(&self) -> u32 {
+        self.bin.iter().filter(|&bit| bit).count() as u32
+    }
+
+    /// Returns Langton's lambda parameter of the rule, the fraction of
+    /// neighborhood states that make the center cell live
+    pub fn lambda(&self) -> f64 {
+        f64::from(self.popcount()) / self.bin.len() as f64
+    }
+
+    /// Splits neighborhood `state` into whether the center is live and how
+    /// many of the other cells the rule looks at are
+    fn center_and_count(&self, state: usize) -> (usize, usize) {
+        let center = if self.mask[4] {
+            let bit = self.mask[..4].iter().filter(|&&m| m).count();
+            (state >> bit) & 1
+        } else {
+            0
+        };
+        (center, state.count_ones() as usize - center)
+    }
+
+    /// Returns the next state of a cell given whether it's live and how many
+    /// of its neighbors are, as `table[center][count]`
+    ///
+    /// Each entry is read off the first neighborhood state with that center
+    /// and count, so the table only describes the whole rule if
+    /// `is_outer_totalistic` holds. Counts the rule's mask can't reach map to
+    /// dead.
+    pub fn response_table(&self) -> [[bool; 9]; 2] {
+        let mut table = [[false; 9]; 2];
+        let mut seen = [[false; 9]; 2];
+        for state in 0..self.bin.len() {
+            let (center, count) = self.center_and_count(state);
+            if !seen[center][count] {
+                seen[center][count] = true;
+                table[center][count] = self.bin[state];
+            }
+        }
+        table
+    }
+
+    /// Returns the rule's whole table, one line per neighborhood state such
+    /// as `16: ... .#. ... -> 0`
+    ///
+    /// Each line shows the state's number, its three rows from top to
+    /// bottom with `#` for a live cell, `.` for a dead one and a space for
+    /// cells the mask leaves out, and the next state of the center.
+    pub fn truth_table(&self) -> String {
+        let mut result = String::new();
+        for state in 0..self.bin.len() {
+            let mut rows = [String::new(), String::new(), String::new()];
+            let mut bit = 0;
+            for position in 0..9 {
+                let c = if !self.mask[position] {
+                    ' '
+                } else {
+                    bit += 1;
+                    if state & (1 << (bit - 1)) != 0 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                };
+                rows[position / 3].push(c);
+            }
+            result.push_str(&format!(
+                "{}: {} {} {} -> {}\n",
+                state, rows[0], rows[1], rows[2], self.bin[state] as u8
+            ));
+        }
+        result
+    }
+
+    /// Returns whether the rule only depends on the center cell and the number
+    /// of live neighbors, not on where they are
+    pub fn is_outer_totalistic(&self) -> bool {
+        let table = self.response_table();
+        (0..self.bin.len()).all(|state| {
+            let (center, count) = self.center_and_count(state);
+            self.bin[state] == table[center][count]
+        })
+    }
+
+    /// Returns the neighborhood states, in increasing order, for which this
+    /// rule and `other` pick different next states
+    ///
+    /// States past the end of the smaller table count as mapping to dead.
+    pub fn differing_states(&self, other: &Rule) -> Vec<usize> {
+        let output = |rule: &Rule, state| rule.bin.get(state).unwrap_or(false);
+        (0..self.bin.len().max(other.bin.len()))
+            .filter(|&state| output(self, state) != output(other, state))
+            .collect()
+    }
+
+    /// Returns every rule that differs from this one in exactly one
+    /// neighborhood state, in order of the state that differs
+    pub fn adjacent_rules(&self) -> impl Iterator<Item = Rule> + '_ {
+        (0..self.bin.len()).map(move |state| self.flipped(state))
+    }
+}
+
+/// A pattern that reappears translated, found by `World::detect_spaceship`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Spaceship {
+    /// Number of generations before the pattern reappears
+    pub period: usize,
+    /// How far the pattern moves each period, positive being right and down
+    pub displacement: (isize, isize),
+}
+
+/// What a world's neighborhoods see past one of its edges
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeMode {
+    /// Cells past the edge are always dead
+    Dead,
+    /// The opposite edge continues past this one, like on a torus
+    Wrap,
+    /// The cells on the edge are repeated past it, as if mirrored
+    Reflect,
+}
+
+/// Edge modes of a world that's dead all around
+const DEAD_EDGES: (EdgeMode, EdgeMode) = (EdgeMode::Dead, EdgeMode::Dead);
+
+/// Summary of a single call to `World::advance`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StepReport {
+    /// Generation the world reached
+    pub generation: usize,
+    /// Number of live cells in that generation
+    pub population: usize,
+    /// Number of cells that were born or died during the step
+    pub activity: usize,
+    /// Whether any cell changed state
+    pub changed: bool,
+}
+
+/// Well-known outer-totalistic rules by name, in B/S notation
+const NAMED_RULES: [(&str, &str); 5] = [
+    ("Conway", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Seeds", "B2/S"),
+    ("Day & Night", "B3678/S34678"),
+    ("Replicator", "B1357/S1357"),
+];
+
+/// Parses one half of a B/S rule, such as `B36`, into the neighbor counts
+/// listed in it
+fn parse_counts(s: &str, prefix: char) -> Option<HashSet<u8>> {
+    let mut chars = s.chars();
+    if chars.next()?.to_ascii_lowercase() != prefix {
+        return None;
+    }
+    let mut counts = HashSet::new();
+    for c in chars {
+        match c.to_digit(10) {
+            Some(n) if n < 9 => counts.insert(n as u8),
+            _ => return None,
+        };
+    }
+    Some(counts)
+}
+
+/// A rule that only looks at whether a cell is live and how many of its 8
+/// neighbors are
+///
+/// Stepping a world with `World::step_with` counts neighbors directly instead
+/// of looking the whole neighborhood up in a 512 entry table.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OuterTotalisticRule {
+    /// Neighbor counts that bring a dead cell to life
+    pub birth: HashSet<u8>,
+    /// Neighbor counts that keep a live cell alive
+    pub survival: HashSet<u8>,
+}
+
+impl OuterTotalisticRule {
+    /// Returns the rule written in B/S notation, such as `B3/S23`, failing if
+    /// `s` isn't in that form
+    pub fn from_bs(s: &str) -> Result<Self, Error> {
+        let mut parts = s.trim().splitn(2, '/');
+        let mut counts = |prefix| parts.next().and_then(|part| parse_counts(part, prefix));
+        match (counts('b'), counts('s')) {
+            (Some(birth), Some(survival)) => Ok(OuterTotalisticRule { birth, survival }),
+            _ => Err(Error::Parse(format!("not a B/S rule: {}", s))),
+        }
+    }
+
+    fn decide_next_state(&self, grid: &Grid, cell: &Coords, edges: (EdgeMode, EdgeMode)) -> bool {
+        let count = count_neighbors(grid, cell, edges);
+        if grid.contains(cell) {
+            self.survival.contains(&count)
+        } else {
+            self.birth.contains(&count)
+        }
+    }
+}
+
+impl From<&OuterTotalisticRule> for Rule {
+    fn from(rule: &OuterTotalisticRule) -> Self {
+        let mut bin = BitVec::from_elem(512, false);
+        for state in 0..512usize {
+            let neighbors = (state & !(1 << 4)).count_ones() as u8;
+            let counts = if state & (1 << 4) == 0 {
+                &rule.birth
+            } else {
+                &rule.survival
+            };
+            bin.set(state, counts.contains(&neighbors));
+        }
+        Rule::from_bits(bin, MOORE)
+    }
+}
+
+/// The characters `World::render_with_style` draws cells with
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Style {
+    pub live: char,
+    pub dead: char,
+}
+
+impl Default for Style {
+    /// Live cells solid and dead ones blank, as `World::render` draws them
+    fn default() -> Self {
+        Style {
+            live: '\u{2588}',
+            dead: ' ',
+        }
+    }
+}
+
+/// Snapshots of a world's grid over a run, made by `World::record`, for
+/// scrubbing back and forth through its history
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Recording {
+    /// Generation of the first frame
+    start: usize,
+    frames: Vec<Grid>,
+}
+
+impl Recording {
+    /// Returns the grid as it was at `generation`, counted like
+    /// `World::generation`, or `None` if that wasn't recorded
+    pub fn frame(&self, generation: usize) -> Option<&Grid> {
+        self.frames.get(generation.checked_sub(self.start)?)
+    }
+
+    /// Returns the number of frames recorded
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns whether no frames were recorded
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Decides the next state of a cell from its neighborhood, so that `World`
+/// can follow rules other than `Rule`
+///
+/// `state` has bit `dx + 3 * dy` set when the cell at offset
+/// `(dx - 1, dy - 1)` is live, the same layout `NeighborhoodMask` uses, so
+/// bit 4 is `center`.
+pub trait Transition {
+    /// Returns whether a cell with the given neighborhood is live next
+    /// generation
+    fn next(&self, center: bool, state: usize) -> bool;
+
+    /// Returns the rule as `table[center][count]`, given whether a cell is
+    /// live and how many of its 8 neighbors are, if that's all it depends on
+    ///
+    /// `World` looks this up once per rule and steps with a plain neighbor
+    /// count when it's there, which is faster than building the state.
+    fn outer_totalistic_table(&self) -> Option<[[bool; 9]; 2]> {
+        None
+    }
+}
+
+impl Transition for Rule {
+    fn next(&self, _center: bool, state: usize) -> bool {
+        // Drop the cells the mask leaves out, packing the rest by rank.
+        let packed = (0..9)
+            .filter(|&i| self.mask[i])
+            .enumerate()
+            .fold(0, |packed, (bit, i)| packed | ((state >> i) & 1) << bit);
+        self.output_for(packed)
+    }
+
+    fn outer_totalistic_table(&self) -> Option<[[bool; 9]; 2]> {
+        if self.mask == MOORE && self.is_outer_totalistic() {
+            Some(self.response_table())
+        } else {
+            None
+        }
+    }
+}
+
+impl Transition for OuterTotalisticRule {
+    fn next(&self, center: bool, state: usize) -> bool {
+        let count = (state & !(1 << 4)).count_ones() as u8;
+        if center {
+            self.survival.contains(&count)
+        } else {
+            self.birth.contains(&count)
+        }
+    }
+
+    fn outer_totalistic_table(&self) -> Option<[[bool; 9]; 2]> {
+        let mut table = [[false; 9]; 2];
+        for (counts, row) in [&self.birth, &self.survival].iter().zip(table.iter_mut()) {
+            for (count, next) in row.iter_mut().enumerate() {
+                *next = counts.contains(&(count as u8));
+            }
+        }
+        Some(table)
+    }
+}
+
+#[derive(Clone)]
+pub struct World<T = Rule> {
+    rule: T,
+    table: Option<[[bool; 9]; 2]>,
+    grid: Grid,
+    swap_grid: Grid,
+    generation: usize,
+    last_activity: usize,
+    auto_shrink: bool,
+    edges: (EdgeMode, EdgeMode),
+    trace: bool,
+    visited: CellSet,
+}
+
+/// Most cells `World::new` and `World::try_new` allow in a world
+pub const MAX_CELLS: usize = 100_000_000;
+
+/// Most pixels `World::try_render_rgba` and `World::render_gif` draw
+pub const MAX_PIXELS: usize = 100_000_000;
+
+/// Longest period `World::fast_forward` looks for
+const FAST_FORWARD_MAX_PERIOD: usize = 64;
+
+/// With auto-shrinking on, `World::step` releases grid memory once a grid has
+/// room for more than this many times its population
+const SHRINK_RATIO: usize = 4;
+
+impl<T: Transition> World<T> {
+    /// Returns an empty world of the given size
+    ///
+    /// Either dimension may be zero. Such a world has no cells, so it stays
+    /// empty: `gen` and `step` leave it unchanged and its population and
+    /// density are both zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world would have more than `MAX_CELLS` cells; use
+    /// `try_new` to handle that instead.
+    pub fn new(width: usize, height: usize, rule: T) -> Self {
+        match World::try_new(width, height, rule) {
+            Ok(world) => world,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Returns an empty world of the given size, or `Error::TooLarge` if it
+    /// would have more than `MAX_CELLS` cells
+    pub fn try_new(width: usize, height: usize, rule: T) -> Result<Self, Error> {
+        World::try_new_with_limit(width, height, rule, MAX_CELLS)
+    }
+
+    /// Returns an empty world of the given size, or `Error::TooLarge` if it
+    /// would have more than `limit` cells
+    pub fn try_new_with_limit(
+        width: usize,
+        height: usize,
+        rule: T,
+        limit: usize,
+    ) -> Result<Self, Error> {
+        match width.checked_mul(height) {
+            Some(cells) if cells <= limit => Ok(World::unchecked(width, height, rule)),
+            _ => Err(Error::TooLarge {
+                width,
+                height,
+                limit,
+            }),
+        }
+    }
+
+    fn unchecked(width: usize, height: usize, rule: T) -> Self {
+        World {
+            table: rule.outer_totalistic_table(),
+            rule,
+            grid: Grid::new(Some((width, height))),
+            swap_grid: Grid::new(Some((width, height))),
+            generation: 0,
+            last_activity: 0,
+            auto_shrink: false,
+            edges: DEAD_EDGES,
+            trace: false,
+            visited: CellSet::new(),
+        }
+    }
+
+    /// Returns a world with the same bounds and live cells as `seed`, which
+    /// must be bounded
+    ///
+    /// Fails with `Error::TooLarge` past `MAX_CELLS`.
+    fn seeded(seed: &Grid, rule: T) -> Result<Self, Error> {
+        let (width, height) = seed.bounds.ok_or(Error::Unbounded)?;
+        let mut world = World::try_new(width, height, rule)?;
+        world.grid = seed.clone();
+        Ok(world)
+    }
+
+    /// Returns the smallest world holding `pattern` with `margin` dead cells
+    /// on every side, the pattern moved so its leftmost and topmost live cells
+    /// sit `margin` cells from the edges
+    ///
+    /// An empty pattern gives a world `2 * margin` cells square. Fails with
+    /// `Error::TooLarge` if the world would have more than `MAX_CELLS` cells.
+    pub fn minimal(pattern: &Grid, rule: T, margin: usize) -> Result<Self, Error> {
+        let (pattern, _) = pattern.normalized();
+        let (width, height) = pattern.extent();
+        // A side that overflows saturates, which `try_new` then rejects.
+        let padded = |side: usize| {
+            margin
+                .checked_mul(2)
+                .and_then(|margins| side.checked_add(margins))
+                .unwrap_or(usize::MAX)
+        };
+        let mut world = World::try_new(padded(width), padded(height), rule)?;
+        for &(x, y) in pattern.grid.iter() {
+            world.grid.insert(&(x + margin, y + margin));
+        }
+        Ok(world)
+    }
+
+    /// Returns a world made of `tiles_x` by `tiles_y` copies of `unit`, side
+    /// by side
+    ///
+    /// Each copy takes up the unit's bounds, or just its extent if it's
+    /// unbounded.
+    ///
+    /// Fails with `Error::TooLarge` if the world would have more than
+    /// `MAX_CELLS` cells.
+    pub fn tiled(unit: &Grid, tiles_x: usize, tiles_y: usize, rule: T) -> Result<Self, Error> {
+        let (width, height) = unit.bounds.unwrap_or_else(|| unit.extent());
+        let too_large = || Error::TooLarge {
+            width: width.saturating_mul(tiles_x),
+            height: height.saturating_mul(tiles_y),
+            limit: MAX_CELLS,
+        };
+        let total_width = width.checked_mul(tiles_x).ok_or_else(too_large)?;
+        let total_height = height.checked_mul(tiles_y).ok_or_else(too_large)?;
+        let mut world = World::try_new(total_width, total_height, rule)?;
+        for ty in 0..tiles_y {
+            for tx in 0..tiles_x {
+                for &(x, y) in unit.grid.iter() {
+                    world.grid.insert(&(tx * width + x, ty * height + y));
+                }
+            }
+        }
+        Ok(world)
+    }
+
+    /// Returns the width and height of the world
+    pub fn dimensions(&self) -> (usize, usize) {
+        // Both grids are created bounded by `World::new` and only ever
+        // swapped with each other or replaced by bounded copies.
+        self.grid.bounds.expect("world grids are bounded")
+    }
+
+    /// Returns the number of columns in the world
+    pub fn width(&self) -> usize {
+        self.dimensions().0
+    }
+
+    /// Returns the number of rows in the world
+    pub fn height(&self) -> usize {
+        self.dimensions().1
+    }
+
+    /// Returns the number of live cells
+    pub fn population(&self) -> usize {
+        self.grid.population()
+    }
+
+    /// Returns the fraction of the world's cells that are live, or zero if
+    /// the world has no cells
+    pub fn density(&self) -> f64 {
+        let (width, height) = self.dimensions();
+        if width == 0 || height == 0 {
+            0.0
+        } else {
+            self.population() as f64 / (width * height) as f64
+        }
+    }
+
+    /// Returns a world with `other` placed to the right of this one
+    ///
+    /// Both worlds must have the same height and follow the same rule, and
+    /// the result no more than `MAX_CELLS` cells.
+    pub fn hstack(&self, other: &Self) -> Result<Self, WorldError>
+    where
+        T: Clone + PartialEq,
+    {
+        let (width, height) = self.dimensions();
+        let (other_width, other_height) = other.dimensions();
+        if other_height != height {
+            return Err(WorldError::SizeMismatch {
+                expected: height,
+                found: other_height,
+            });
+        }
+        self.stack(other, (width.checked_add(other_width), Some(height)), (width, 0))
+    }
+
+    /// Returns a world with `other` placed below this one
+    ///
+    /// Both worlds must have the same width and follow the same rule, and
+    /// the result no more than `MAX_CELLS` cells.
+    pub fn vstack(&self, other: &Self) -> Result<Self, WorldError>
+    where
+        T: Clone + PartialEq,
+    {
+        let (width, height) = self.dimensions();
+        let (other_width, other_height) = other.dimensions();
+        if other_width != width {
+            return Err(WorldError::SizeMismatch {
+                expected: width,
+                found: other_width,
+            });
+        }
+        self.stack(other, (Some(width), height.checked_add(other_height)), (0, height))
+    }
+
+    /// Returns a world `size` cells big, `None` for a side that overflowed,
+    /// holding this world's cells and `other`'s moved by `(dx, dy)`
+    fn stack(
+        &self,
+        other: &Self,
+        size: (Option<usize>, Option<usize>),
+        (dx, dy): (usize, usize),
+    ) -> Result<Self, WorldError>
+    where
+        T: Clone + PartialEq,
+    {
+        if other.rule != self.rule {
+            return Err(WorldError::RuleMismatch);
+        }
+        let (width, height) = (size.0.unwrap_or(usize::MAX), size.1.unwrap_or(usize::MAX));
+        let too_large = WorldError::TooLarge {
+            width,
+            height,
+            limit: MAX_CELLS,
+        };
+        if size.0.is_none() || size.1.is_none() {
+            return Err(too_large);
+        }
+        let mut result =
+            World::try_new(width, height, self.rule.clone()).map_err(|_| too_large)?;
+        for cell in self.grid.grid.iter() {
+            result.grid.insert(cell);
+        }
+        for &(x, y) in other.grid.grid.iter() {
+            result.grid.insert(&(x + dx, y + dy));
+        }
+        Ok(result)
+    }
+
+    /// Returns the edge modes of the world's left and right edges and of its
+    /// top and bottom ones
+    pub fn edges(&self) -> (EdgeMode, EdgeMode) {
+        self.edges
+    }
+
+    /// Sets what lies past the world's edges along each axis, taking effect
+    /// on the next `step`
+    ///
+    /// Worlds start out dead past every edge. Wrapping `x_edge` only turns
+    /// the world into a cylinder, wrapping both into a torus.
+    pub fn set_edges(&mut self, x_edge: EdgeMode, y_edge: EdgeMode) {
+        self.edges = (x_edge, y_edge);
+    }
+
+    /// Returns the rule currently driving the world
+    pub fn rule(&self) -> &T {
+        &self.rule
+    }
+
+    /// Replaces the rule driving the world, taking effect on the next `step`
+    ///
+    /// The new rule may look at a different neighborhood than the old one.
+    pub fn set_rule(&mut self, rule: T) {
+        self.table = rule.outer_totalistic_table();
+        self.rule = rule;
+    }
+
+    /// Returns whether `cell` will be live after the next `step`
+    pub fn next_state(&self, cell: &Coords) -> bool {
+        let state = get_state(&self.grid, cell, &MOORE, self.edges);
+        self.rule.next(self.grid.contains(cell), state)
+    }
+
+    pub fn step(&mut self) {
+        match self.table {
+            Some(table) => self.step_by(|world, cell| {
+                let count = count_neighbors(&world.grid, cell, world.edges);
+                table[world.grid.contains(cell) as usize][count as usize]
+            }),
+            None => self.step_by(|world, cell| world.next_state(cell)),
+        }
+    }
+
+    /// Steps only the cells in the rectangle from `top_left` to
+    /// `bottom_right`, both included, leaving every other cell as it is
+    ///
+    /// Cells inside read their neighbors from the whole world, so those on
+    /// the rectangle's border see the frozen cells around it. It still counts
+    /// as a generation.
+    pub fn step_region(&mut self, top_left: Cell, bottom_right: Cell) {
+        let (width, height) = self.dimensions();
+        let xs = top_left.x..(bottom_right.x.saturating_add(1)).min(width);
+        let ys = top_left.y..(bottom_right.y.saturating_add(1)).min(height);
+        let mut next = self.take_swap_grid();
+        for &(x, y) in self.grid.grid.iter() {
+            if !xs.contains(&x) || !ys.contains(&y) {
+                next.insert(&(x, y));
+            }
+        }
+        for x in xs.clone() {
+            for y in ys.clone() {
+                if self.next_state(&(x, y)) {
+                    next.insert(&(x, y));
+                }
+            }
+        }
+        self.finish_step(next);
+    }
+
+    /// Makes every cell in the rectangle from `top_left` to `bottom_right`,
+    /// both included, live if `alive` is set and dead otherwise
+    ///
+    /// The parts of the rectangle outside the world are ignored.
+    pub fn fill_rect(&mut self, top_left: Cell, bottom_right: Cell, alive: bool) {
+        let (width, height) = self.dimensions();
+        for x in top_left.x..(bottom_right.x.saturating_add(1)).min(width) {
+            for y in top_left.y..(bottom_right.y.saturating_add(1)).min(height) {
+                if alive {
+                    self.grid.insert(&(x, y));
+                } else {
+                    self.grid.grid.remove(&(x, y));
+                }
+            }
+        }
+    }
+
+    /// Steps the world `n` times
+    pub fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    /// Steps the world `generations` times, returning the generation it
+    /// reached, but stops with an error as soon as a step leaves more than
+    /// `max_population` cells live
+    pub fn run_capped(
+        &mut self,
+        generations: usize,
+        max_population: usize,
+    ) -> Result<usize, PopulationExceeded> {
+        for _ in 0..generations {
+            self.step();
+            let population = self.population();
+            if population > max_population {
+                return Err(PopulationExceeded {
+                    generation: self.generation,
+                    population,
+                    limit: max_population,
+                });
+            }
+        }
+        Ok(self.generation)
+    }
+
+    /// Returns the 4x4 block of cells from `(bx - 1, by - 1)` to
+    /// `(bx + 2, by + 2)`, with bit `x + 4 * y` set for each live one
+    ///
+    /// Cells past the edges are found as `step` would find them from within
+    /// the 2x2 block at `(bx, by)`.
+    fn block_at(&self, bx: usize, by: usize) -> u16 {
+        let (width, height) = self.dimensions();
+        let mut block = 0;
+        for wx in 0..4 {
+            for wy in 0..4 {
+                // The last row and column are seen from the far side of the
+                // 2x2 block, which may itself lie past the edge.
+                let anchor = Cell {
+                    x: bx + (wx == 3) as usize,
+                    y: by + (wy == 3) as usize,
+                };
+                if anchor.x >= width || anchor.y >= height {
+                    continue;
+                }
+                let dx = wx as isize - 1 - (wx == 3) as isize;
+                let dy = wy as isize - 1 - (wy == 3) as isize;
+                let live = neighbor_at(&self.grid, anchor, dx, dy, self.edges)
+                    .is_some_and(|cell| self.grid.contains(&cell.into()));
+                if live {
+                    block |= 1 << (wx + 4 * wy);
+                }
+            }
+        }
+        block
+    }
+
+    /// Steps the world following `rule` instead of the world's own rule
+    pub fn step_with(&mut self, rule: &OuterTotalisticRule) {
+        self.step_by(|world, cell| rule.decide_next_state(&world.grid, cell, world.edges));
+    }
+
+    /// Steps the world following `rule`, which is given whether a cell is
+    /// live and how many of its 8 neighbors are, and returns whether it's
+    /// live in the next generation
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut world = hemoglobin::World::new(5, 5, hemoglobin::Rule::named("Conway").unwrap());
+    /// // Seeds: every live cell dies, dead cells with two live neighbors are born.
+    /// world.step_fn(|alive, count| !alive && count == 2);
+    /// ```
+    pub fn step_fn<F: Fn(bool, u8) -> bool>(&mut self, rule: F) {
+        self.step_by(|world, cell| {
+            rule(world.grid.contains(cell), count_neighbors(&world.grid, cell, world.edges))
+        });
+    }
+
+    /// Steps the world, making live the cells `decide` picks given the world
+    /// as it was before the step
+    fn step_by<F: Fn(&Self, &Coords) -> bool>(&mut self, decide: F) {
+        let mut next = self.take_swap_grid();
+        self.fill_next(&mut next, decide);
+        self.finish_step(next);
+    }
+
+    /// Returns the emptied swap grid, to fill with the next generation
+    fn take_swap_grid(&mut self) -> Grid {
+        let mut next = std::mem::replace(&mut self.swap_grid, Grid::new(None));
+        next.grid.clear();
+        next
+    }
+
+    /// Makes `next` the current generation and updates the world's records
+    fn finish_step(&mut self, next: Grid) {
+        self.swap_grid = std::mem::replace(&mut self.grid, next);
+        self.generation += 1;
+        self.last_activity = self.grid.hamming_distance(&self.swap_grid);
+        if self.trace {
+            self.visited.extend(self.grid.grid.iter());
+        }
+        if self.auto_shrink && self.grid.capacity() > SHRINK_RATIO * self.population() {
+            self.grid.shrink_to_fit();
+            self.swap_grid.shrink_to_fit();
+        }
+    }
+
+    /// Makes live in `next` the cells `decide` picks
+    fn fill_next<F: Fn(&Self, &Coords) -> bool>(&self, next: &mut Grid, decide: F) {
+        let (width, height) = self.dimensions();
+        for x in 0..width {
+            for y in 0..height {
+                let cell = (x, y);
+                if decide(self, &cell) {
+                    next.insert(&cell);
+                }
+            }
+        }
+    }
+
+    /// Returns the grid `step` would produce, leaving the world untouched
+    pub fn next_grid(&self) -> Grid {
+        let mut next = Grid::new(self.grid.bounds);
+        self.fill_next(&mut next, |world, cell| world.next_state(cell));
+        next
+    }
+
+    /// Sets whether `step` gives memory back once the population has dropped
+    /// far below what the grids have room for
+    ///
+    /// Off by default, since a population that grows back has to allocate
+    /// again.
+    pub fn set_auto_shrink(&mut self, on: bool) {
+        self.auto_shrink = on;
+    }
+
+    /// Sets whether `step` keeps track of every cell that has been live, for
+    /// `visited` and `render_trace`
+    ///
+    /// Off by default, since the record only ever grows. Turning it on starts
+    /// the record from the live cells; turning it off drops it.
+    pub fn set_trace(&mut self, on: bool) {
+        if on != self.trace {
+            self.trace = on;
+            self.restart_trace();
+        }
+    }
+
+    /// Starts the record of visited cells over from the current live cells,
+    /// or leaves it empty when tracing is off
+    fn restart_trace(&mut self) {
+        self.visited.clear();
+        if self.trace {
+            self.visited.extend(self.grid.grid.iter());
+        }
+    }
+
+    /// Steps the world and reports what happened
+    pub fn advance(&mut self) -> StepReport {
+        self.step();
+        StepReport {
+            generation: self.generation,
+            population: self.population(),
+            activity: self.last_activity,
+            changed: self.last_activity != 0,
+        }
+    }
+
+    /// Brings the world to generation `target_gen`, skipping whole periods
+    /// once the evolution turns out to be periodic
+    ///
+    /// Periods of up to `FAST_FORWARD_MAX_PERIOD` generations are detected;
+    /// anything else is stepped through generation by generation. Does
+    /// nothing if the world is already at or past `target_gen`.
+    pub fn fast_forward(&mut self, target_gen: usize) {
+        let mut history = vec![self.grid.clone()];
+        while self.generation < target_gen {
+            self.step();
+            if history.len() > FAST_FORWARD_MAX_PERIOD {
+                continue;
+            }
+            match history.iter().position(|grid| *grid == self.grid) {
+                Some(start) => {
+                    let period = history.len() - start;
+                    for _ in 0..(target_gen - self.generation) % period {
+                        self.step();
+                    }
+                    self.generation = target_gen;
+                }
+                None => history.push(self.grid.clone()),
+            }
+        }
+    }
+
+    /// Returns the number of live neighbors of every cell, as rows of
+    /// `map[y][x]`, taking the world's edge modes into account
+    pub fn neighbor_count_map(&self) -> Vec<Vec<u8>> {
+        let (width, height) = self.dimensions();
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| count_neighbors(&self.grid, &(x, y), self.edges))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Steps the world `generations` times, returning the activity of each
+    /// step
+    pub fn activity_log(&mut self, generations: usize) -> Vec<usize> {
+        (0..generations)
+            .map(|_| {
+                self.step();
+                self.last_activity
+            })
+            .collect()
+    }
+
+    /// Steps the world `generations` times, returning how many times every
+    /// cell changed state, as rows of `map[y][x]`
+    pub fn heat_map(&mut self, generations: usize) -> Vec<Vec<u32>> {
+        let (width, height) = self.dimensions();
+        let mut heat = vec![vec![0; width]; height];
+        for _ in 0..generations {
+            let before = self.grid.grid.clone();
+            self.step();
+            for &(x, y) in before.symmetric_difference(&self.grid.grid) {
+                heat[y][x] += 1;
+            }
+        }
+        heat
+    }
+
+    /// Steps the world up to `max_period` times looking for the current
+    /// pattern to reappear somewhere else
+    ///
+    /// Stops at the first generation where the pattern repeats, so still
+    /// lifes and oscillators, which repeat in place, yield `None`, as does an
+    /// empty world.
+    pub fn detect_spaceship(&mut self, max_period: usize) -> Option<Spaceship> {
+        if self.population() == 0 {
+            return None;
+        }
+        let (pattern, (x0, y0)) = self.grid.normalized();
+        for period in 1..=max_period {
+            self.step();
+            let (current, (x, y)) = self.grid.normalized();
+            if current == pattern {
+                let displacement = (x as isize - x0 as isize, y as isize - y0 as isize);
+                if displacement == (0, 0) {
+                    return None;
+                }
+                return Some(Spaceship {
+                    period,
+                    displacement,
+                });
+            }
+        }
+        None
+    }
+
+    /// Steps the world until `cell` turns live and returns the generation it
+    /// did, or `None` if it's still dead once the world reaches generation
+    /// `max_gen`
+    ///
+    /// A cell that's already live gives the current generation without
+    /// stepping.
+    pub fn first_activation(&mut self, cell: Cell, max_gen: usize) -> Option<usize> {
+        let cell = Coords::from(cell);
+        loop {
+            if self.grid.contains(&cell) {
+                return Some(self.generation);
+            }
+            if self.generation >= max_gen {
+                return None;
+            }
+            self.step();
+        }
+    }
+
+    /// Steps the world until it comes back to a state it's been in, and
+    /// returns how many distinct states it went through, counting the one it
+    /// started in
+    ///
+    /// Gives `None`, after `max_steps` steps, if no state repeated by then.
+    pub fn orbit_size(&mut self, max_steps: usize) -> Option<usize> {
+        let mut seen = HashSet::new();
+        seen.insert(self.grid.iter_sorted().collect::<Vec<_>>());
+        for _ in 0..max_steps {
+            self.step();
+            if !seen.insert(self.grid.iter_sorted().collect()) {
+                return Some(seen.len());
+            }
+        }
+        None
+    }
+
+    /// Steps the world `generations` times, writing the current generation and
+    /// every following one to `path` as frames of an animated GIF
+    ///
+    /// Cells are drawn as black or white squares of `cell_px` pixels and each
+    /// frame lasts `delay_ms` milliseconds, rounded down to the GIF's
+    /// hundredths of a second. If the pattern dies out the animation ends on
+    /// the first empty frame. Fails with `GifError::TooLarge` if a frame would
+    /// be wider or taller than a GIF allows or have more than `MAX_PIXELS`
+    /// pixels.
+    #[cfg(feature = "gif")]
+    pub fn render_gif(
+        &mut self,
+        path: &Path,
+        generations: usize,
+        cell_px: u32,
+        delay_ms: u16,
+    ) -> Result<(), GifError> {
+        let (width, height) = self.dimensions();
+        let to_px = |cells: usize| {
+            (cells as u64)
+                .checked_mul(u64::from(cell_px))
+                .filter(|&px| px <= u64::from(u16::MAX))
+                .map(|px| px as u16)
+                .ok_or(GifError::TooLarge)
+        };
+        let (width_px, height_px) = (to_px(width)?, to_px(height)?);
+        self.grid
+            .raster_size(cell_px)
+            .map_err(|_| GifError::TooLarge)?;
+
+        let file = BufWriter::new(File::create(path)?);
+        let palette = [0xff, 0xff, 0xff, 0x00, 0x00, 0x00];
+        let mut encoder = gif::Encoder::new(file, width_px, height_px, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for generation in 0..=generations {
+            if generation > 0 {
+                self.step();
+            }
+            let frame = gif::Frame {
+                width: width_px,
+                height: height_px,
+                delay: delay_ms / 10,
+                buffer: self
+                    .grid
+                    .rasterize(cell_px)
+                    .map_err(|_| GifError::TooLarge)?
+                    .into(),
+                ..gif::Frame::default()
+            };
+            encoder.write_frame(&frame)?;
+            if self.population() == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the width and height in pixels of the world drawn with every
+    /// cell as a square of `cell_px` pixels, and its RGBA bytes, row by row,
+    /// ready to copy onto an HTML canvas
+    ///
+    /// Live cells are opaque black and dead ones opaque white.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image would be wider or taller than a `u32` allows or
+    /// have more than `MAX_PIXELS` pixels; use `try_render_rgba` to handle
+    /// that instead.
+    pub fn render_rgba(&self, cell_px: u32) -> (u32, u32, Vec<u8>) {
+        match self.try_render_rgba(cell_px) {
+            Ok(image) => image,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Returns the world drawn as by `render_rgba`, or `Error::TooLarge` if
+    /// the image would be wider or taller than a `u32` allows or have more
+    /// than `MAX_PIXELS` pixels
+    pub fn try_render_rgba(&self, cell_px: u32) -> Result<(u32, u32, Vec<u8>), Error> {
+        let (width_px, height_px) = self.grid.raster_size(cell_px)?;
+        let rgba = self
+            .grid
+            .rasterize(cell_px)?
+            .into_iter()
+            .flat_map(|live| if live == 1 { [0, 0, 0, 0xff] } else { [0xff; 4] })
+            .collect();
+        Ok((width_px, height_px, rgba))
+    }
+
+    /// Returns every cell that has been live in any generation stepped
+    /// through since the world was last seeded, or tracing was turned on with
+    /// `set_trace`
+    ///
+    /// Always empty while tracing is off.
+    pub fn visited(&self) -> &HashSet<Coords> {
+        &self.visited
+    }
+
+    /// Returns the number of steps taken since the world was last seeded
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns the number of cells that were born or died during the last step
+    pub fn last_activity(&self) -> usize {
+        self.last_activity
+    }
+
+    /// Steps the world `generations` times and returns a JSON array with the
+    /// `generation`, `population` and `bbox`, the `width` and `height` of
+    /// the live cells' bounding box, before the first step and after each
+    #[cfg(feature = "serde")]
+    pub fn run_to_json(&mut self, generations: usize) -> String {
+        let mut entries = Vec::with_capacity(generations + 1);
+        for i in 0..=generations {
+            if i > 0 {
+                self.step();
+            }
+            let (width, height) = self.grid.normalized().0.extent();
+            entries.push(serde_json::json!({
+                "generation": self.generation,
+                "population": self.population(),
+                "bbox": { "width": width, "height": height },
+            }));
+        }
+        serde_json::Value::Array(entries).to_string()
+    }
+
+    #[cfg(feature = "random")]
+    pub fn gen(&mut self) {
+        self.gen_with_border(0);
+    }
+
+    /// Fills the world with random live cells, keeping those within `border`
+    /// cells of an edge dead, and restarts its generation count
+    #[cfg(feature = "random")]
+    pub fn gen_with_border(&mut self, border: usize) {
+        self.grid
+            .gen_with_border(border)
+            .expect("world grids are bounded");
+        self.generation = 0;
+        self.last_activity = 0;
+        self.restart_trace();
+    }
+
+    /// Fills the world with random live cells scattered as `mode` says,
+    /// drawn reproducibly from `seed`, and restarts its generation count
+    #[cfg(feature = "random")]
+    pub fn gen_mode(&mut self, mode: SeedMode, seed: u64) {
+        self.grid
+            .gen_mode(mode, seed)
+            .expect("world grids are bounded");
+        self.generation = 0;
+        self.last_activity = 0;
+        self.restart_trace();
+    }
+
+    /// Replaces the world's cells with those of `pattern`, at the same
+    /// coordinates, and restarts its generation count
+    ///
+    /// Cells outside the world are handled as `on_overflow` says; with
+    /// `OverflowPolicy::Error` the failure names the topmost, then leftmost,
+    /// such cell. Resizing fails with `Error::TooLarge` past `MAX_CELLS`.
+    pub fn load(&mut self, pattern: &Grid, on_overflow: OverflowPolicy) -> Result<(), Error> {
+        let (width, height) = self.dimensions();
+        let (width, height) = match on_overflow {
+            OverflowPolicy::Clip => (width, height),
+            OverflowPolicy::Error => {
+                let outside = pattern
+                    .grid
+                    .iter()
+                    .filter(|&&(x, y)| x >= width || y >= height)
+                    .min_by_key(|&&(x, y)| (y, x));
+                if let Some(&cell) = outside {
+                    return Err(Error::OutOfBounds(OutOfBounds {
+                        cell,
+                        bounds: (width, height),
+                    }));
+                }
+                (width, height)
+            }
+            OverflowPolicy::Resize => {
+                // Only the live cells count, not a bounded pattern's bounds.
+                let w = pattern.grid.iter().map(|&(x, _)| x.saturating_add(1)).max().unwrap_or(0);
+                let h = pattern.grid.iter().map(|&(_, y)| y.saturating_add(1)).max().unwrap_or(0);
+                let (width, height) = (width.max(w), height.max(h));
+                match width.checked_mul(height) {
+                    Some(cells) if cells <= MAX_CELLS => (width, height),
+                    _ => {
+                        return Err(Error::TooLarge {
+                            width,
+                            height,
+                            limit: MAX_CELLS,
+                        })
+                    }
+                }
+            }
+        };
+        self.grid = pattern.with_bounds(Some((width, height)));
+        self.swap_grid = Grid::new(Some((width, height)));
+        self.generation = 0;
+        self.last_activity = 0;
+        self.restart_trace();
+        Ok(())
+    }
+
+    /// Kills every cell and restarts the generation count, keeping the rule,
+    /// size and edges
+    pub fn reset(&mut self) {
+        self.grid.grid.clear();
+        self.swap_grid.grid.clear();
+        self.generation = 0;
+        self.last_activity = 0;
+        self.restart_trace();
+    }
+
+    /// Steps the world `max_gen` times at roughly `gps` generations per
+    /// second, calling `on_frame` with the new grid after every step
+    ///
+    /// Time spent in `on_frame` counts against the frame budget, so slow
+    /// rendering doesn't slow the simulation down unless it overruns it.
+    pub fn run_realtime<F: FnMut(&Grid)>(&mut self, gps: f64, max_gen: usize, mut on_frame: F) {
+        let frame = frame_duration(gps);
+        for _ in 0..max_gen {
+            let start = Instant::now();
+            self.step();
+            on_frame(&self.grid);
+            let elapsed = start.elapsed();
+            if elapsed < frame {
+                thread::sleep(frame - elapsed);
+            }
+        }
+    }
+
+    /// Steps the world `generations` times, keeping a copy of the grid before
+    /// the first step and after every one
+    pub fn record(&mut self, generations: usize) -> Recording {
+        let start = self.generation;
+        let mut frames = Vec::with_capacity(generations + 1);
+        frames.push(self.grid.clone());
+        for _ in 0..generations {
+            self.step();
+            frames.push(self.grid.clone());
+        }
+        Recording { start, frames }
+    }
+
+    /// Moves the world to a new thread that steps it `generations` times,
+    /// sending a copy of the grid down the returned channel after every step
+    ///
+    /// The thread doesn't wait for the receiver, so grids pile up in the
+    /// channel if they're read slower than they're made. It stops early if
+    /// the receiver is dropped.
+    pub fn spawn_simulation(mut self, generations: usize) -> mpsc::Receiver<Grid>
+    where
+        T: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for _ in 0..generations {
+                self.step();
+                if sender.send(self.grid.clone()).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Draws the world with live cells solid
+    ///
+    /// Whatever doesn't fit on the canvas is left out.
+    #[cfg(feature = "terminal")]
+    pub fn render(&self, canvas: &mut Widget) {
+        self.render_with_style(canvas, &Style::default());
+    }
+
+    /// Draws the world with the characters chosen in `style`
+    ///
+    /// Whatever doesn't fit on the canvas is left out.
+    #[cfg(feature = "terminal")]
+    pub fn render_with_style(&self, canvas: &mut Widget, style: &Style) {
+        let (width, height) = self.dimensions();
+        for x in 0..width {
+            for y in 0..height {
+                if let Some(cell) = canvas.get_mut(x, y) {
+                    if self.grid.contains(&(x, y)) {
+                        cell.set_ch(style.live);
+                    } else {
+                        cell.set_ch(style.dead);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws the world with live cells solid and cells that have been live
+    /// before, according to `visited`, shaded
+    ///
+    /// Only live cells show unless tracing is on; see `set_trace`.
+    /// Whatever doesn't fit on the canvas is left out.
+    #[cfg(feature = "terminal")]
+    pub fn render_trace(&self, canvas: &mut Widget) {
+        let (width, height) = self.dimensions();
+        for x in 0..width {
+            for y in 0..height {
+                if let Some(cell) = canvas.get_mut(x, y) {
+                    if self.grid.contains(&(x, y)) {
+                        cell.set_ch('\u{2588}');
+                    } else if self.visited.contains(&(x, y)) {
+                        cell.set_ch('\u{2591}');
+                    } else {
+                        cell.set_ch(' ');
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws the world like `render`, one row and column in from the top left
+    /// corner, with a faint tick every `spacing` cells along the top row and
+    /// the left column, starting from the world's first cell
+    ///
+    /// Whatever doesn't fit on the canvas is left out.
+    #[cfg(feature = "terminal")]
+    pub fn render_with_ruler(&self, canvas: &mut Widget, spacing: usize) {
+        let (width, height) = self.dimensions();
+        let mark = |ticks: &[usize], i: usize| {
+            if ticks.contains(&i) {
+                '\u{b7}'
+            } else {
+                ' '
+            }
+        };
+        let columns = ticks(width, spacing);
+        for x in 0..width {
+            if let Some(cell) = canvas.get_mut(x + 1, 0) {
+                cell.set_ch(mark(&columns, x));
+            }
+        }
+        let rows = ticks(height, spacing);
+        for y in 0..height {
+            if let Some(cell) = canvas.get_mut(0, y + 1) {
+                cell.set_ch(mark(&rows, y));
+            }
+        }
+        for x in 0..width {
+            for y in 0..height {
+                if let Some(cell) = canvas.get_mut(x + 1, y + 1) {
+                    if self.grid.contains(&(x, y)) {
+                        cell.set_ch('\u{2588}');
+                    } else {
+                        cell.set_ch(' ');
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws the whole world shrunk to fit the canvas, shading each character
+    /// by how many of the cells it covers are live
+    #[cfg(feature = "terminal")]
+    pub fn render_downscaled(&self, canvas: &mut Widget) {
+        let (width, height) = self.dimensions();
+        let (canvas_width, canvas_height) = canvas.size();
+        // The cells under canvas position `c` along an axis, with at least one
+        // cell each when the canvas is the larger of the two.
+        let span = |c: usize, size: usize, canvas_size: usize| {
+            let start = c * size / canvas_size;
+            let end = ((c + 1) * size / canvas_size).max(start + 1).min(size);
+            start..end
+        };
+        for cx in 0..canvas_width {
+            for cy in 0..canvas_height {
+                let xs = span(cx, width, canvas_width);
+                let ys = span(cy, height, canvas_height);
+                let total = xs.len() * ys.len();
+                let live = xs
+                    .flat_map(|x| ys.clone().map(move |y| (x, y)))
+                    .filter(|cell| self.grid.contains(cell))
+                    .count();
+                let fraction = if total == 0 {
+                    0.0
+                } else {
+                    live as f64 / total as f64
+                };
+                if let Some(cell) = canvas.get_mut(cx, cy) {
+                    cell.set_ch(shade(fraction));
+                }
+            }
+        }
+    }
+
+    /// Returns the world cell shown at `canvas_coord` when the canvas's top
+    /// left corner shows `origin`, or `None` if that's outside the world
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hemoglobin::{Cell, Rule, World};
+    /// let world = World::new(10, 10, Rule::named("Conway").unwrap());
+    /// let origin = Cell { x: 4, y: 2 };
+    /// assert_eq!(world.viewport_to_cell((1, 3), origin), Some(Cell { x: 5, y: 5 }));
+    /// assert_eq!(world.viewport_to_cell((6, 0), origin), None);
+    /// ```
+    pub fn viewport_to_cell(&self, canvas_coord: (usize, usize), origin: Cell) -> Option<Cell> {
+        let (width, height) = self.dimensions();
+        let cell = Cell {
+            x: origin.x.checked_add(canvas_coord.0)?,
+            y: origin.y.checked_add(canvas_coord.1)?,
+        };
+        if cell.x < width && cell.y < height {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    /// Draws `tiles_x` by `tiles_y` copies of the world side by side, showing
+    /// how patterns carry on across wrapping edges
+    ///
+    /// Whatever doesn't fit on the canvas is left out.
+    #[cfg(feature = "terminal")]
+    pub fn render_tiled(&self, canvas: &mut Widget, tiles_x: usize, tiles_y: usize) {
+        let (width, height) = self.dimensions();
+        let (canvas_width, canvas_height) = canvas.size();
+        let columns = width.saturating_mul(tiles_x).min(canvas_width);
+        let rows = height.saturating_mul(tiles_y).min(canvas_height);
+        for x in 0..columns {
+            for y in 0..rows {
+                if let Some(cell) = canvas.get_mut(x, y) {
+                    if self.grid.contains(&(fold(x, width), fold(y, height))) {
+                        cell.set_ch('\u{2588}');
+                    } else {
+                        cell.set_ch(' ');
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl World {
+    /// Steps the world `k` times, like `step_n`, but works out each
+    /// generation 2x2 cells at a time from the 4x4 block around them,
+    /// remembering the outcome of every block seen during the call
+    ///
+    /// Patterns made of few distinct blocks, such as large repetitive ones,
+    /// need far fewer rule lookups this way.
+    pub fn compose_step(&mut self, k: usize) {
+        let mut memo = HashMap::new();
+        for _ in 0..k {
+            let mut next = self.take_swap_grid();
+            let (width, height) = self.dimensions();
+            for bx in (0..width).step_by(2) {
+                for by in (0..height).step_by(2) {
+                    let block = self.block_at(bx, by);
+                    let outcome = *memo
+                        .entry(block)
+                        .or_insert_with(|| self.rule.block_outcome(block));
+                    for i in 0..4 {
+                        if outcome & (1 << i) != 0 {
+                            next.insert(&(bx + i % 2, by + i / 2));
+                        }
+                    }
+                }
+            }
+            self.finish_step(next);
+        }
+    }
+
+    /// Returns a one-line summary of the world for status bars, such as
+    /// `gen 412 | pop 173 | Δ 12 | rule B3/S23`
+    ///
+    /// `Δ` is `last_activity`. Rules `Rule::named` knows are written in B/S
+    /// notation and the rest as their number.
+    pub fn status_line(&self) -> String {
+        let rule = match self.rule.known_bs() {
+            Some(bs) => bs.to_string(),
+            None => self.rule.to_string(),
+        };
+        format!(
+            "gen {} | pop {} | \u{394} {} | rule {}",
+            self.generation,
+            self.population(),
+            self.last_activity,
+            rule
+        )
+    }
+
+    /// Steps the world `settle_gen` times and tallies the objects left over,
+    /// keyed by apgcode, such as `xs4_33` for each block
+    ///
+    /// Objects are the groups of touching live cells, each named on its own
+    /// by `Grid::to_apgcode` with the world's rule. Those that don't repeat
+    /// within `APGCODE_MAX_PERIOD` generations are counted as `unknown`.
+    pub fn ash_census(&mut self, settle_gen: usize) -> HashMap<String, usize> {
+        self.step_n(settle_gen);
+        let mut census = HashMap::new();
+        for object in self.grid.components() {
+            let code = object
+                .to_apgcode(&self.rule)
+                .unwrap_or_else(|| "unknown".to_string());
+            *census.entry(code).or_insert(0) += 1;
+        }
+        census
+    }
+}
+
+/// Number of generations a candidate rule is run for by `search_rule` before
+/// its fitness is measured
+#[cfg(feature = "random")]
+const SEARCH_GENERATIONS: usize = 32;
+
+/// Searches rule space for a rule that scores well under `fitness`
+///
+/// Starting from a sparse random rule drawn from `seed_rng` (uniformly random
+/// rules almost always flood the grid), every iteration picks a random rule
+/// out of the current best's `adjacent_rules`, runs a world seeded with `seed`
+/// under it for `SEARCH_GENERATIONS` generations and keeps it if `fitness`
+/// scores the resulting world higher than the best so far.
+///
+/// Fails with `Error::Unbounded` if `seed` is unbounded and with
+/// `Error::TooLarge` if it has more than `MAX_CELLS` cells.
+#[cfg(feature = "random")]
+pub fn search_rule(
+    seed: &Grid,
+    fitness: impl Fn(&World) -> f64,
+    iterations: usize,
+    seed_rng: u64,
+) -> Result<Rule, Error> {
+    let seeded: World = World::seeded(seed, Rule::from(BigUint::from(0u32)))?;
+    let mut rng = StdRng::seed_from_u64(seed_rng);
+    let score = |rule: Rule| {
+        let mut world = seeded.clone();
+        world.set_rule(rule);
+        for _ in 0..SEARCH_GENERATIONS {
+            world.step();
+        }
+        fitness(&world)
+    };
+
+    let mut bin = BitVec::from_elem(512, false);
+    for state in 0..bin.len() {
+        bin.set(state, rng.gen_bool(0.1));
+    }
+    let mut best = Rule::from_bits(bin, MOORE);
+    let mut best_score = score(best.clone());
+    for _ in 0..iterations {
+        // Equivalent to drawing from `best.adjacent_rules()`, without building
+        // every neighbor first.
+        let candidate = best.flipped(rng.gen_range(0, best.bin.len()));
+        let candidate_score = score(candidate.clone());
+        if candidate_score > best_score {
+            best = candidate;
+            best_score = candidate_score;
+        }
+    }
+    Ok(best)
+}
+
+/// Checks whether `rule` fills space: a `size` by `size` world seeded with
+/// a dense central soup drawn from `seed` is run for up to `generations`
+/// generations, stopping as soon as the fraction of live cells exceeds
+/// `fill_threshold`
+///
+/// Only densities after a step count, so a threshold below that of the soup
+/// doesn't make every rule explosive. Fails with `Error::TooLarge` if the
+/// world would have more than `MAX_CELLS` cells.
+#[cfg(feature = "random")]
+pub fn is_explosive(
+    rule: &Rule,
+    seed: u64,
+    size: usize,
+    generations: usize,
+    fill_threshold: f64,
+) -> Result<bool, Error> {
+    let mut world: World = World::try_new(size, size, rule.clone())?;
+    world.gen_mode(SeedMode::Centered, seed);
+    // `try_new` has already checked that this doesn't overflow.
+    let cells = (size * size) as f64;
+    for _ in 0..generations {
+        world.step();
+        if world.population() as f64 > fill_threshold * cells {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Measures how fast `rule` grows: a `size` by `size` world seeded with a
+/// dense central soup drawn from `seed` is run for `generations` generations,
+/// and the result is the slope of a least-squares line through the
+/// population over the second half of the run
+///
+/// Explosive rules score high, rules whose soups settle score about zero and
+/// dying rules score below zero. Fails with `Error::TooLarge` if the world
+/// would have more than `MAX_CELLS` cells.
+#[cfg(feature = "random")]
+pub fn growth_rate(rule: &Rule, size: usize, generations: usize, seed: u64) -> Result<f64, Error> {
+    let mut world: World = World::try_new(size, size, rule.clone())?;
+    world.gen_mode(SeedMode::Centered, seed);
+    let mut populations = Vec::with_capacity(generations);
+    for _ in 0..generations {
+        world.step();
+        populations.push(world.population() as f64);
+    }
+    let tail = &populations[generations / 2..];
+    if tail.len() < 2 {
+        return Ok(0.0);
+    }
+    let n = tail.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = tail.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (x, y) in tail.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+    Ok(covariance / variance)
+}
+
+/// Weight of the activity's variation in `interestingness`
+pub const INTERESTINGNESS_ACTIVITY_WEIGHT: f64 = 0.5;
+
+/// Weight of the live bounding box's size in `interestingness`
+pub const INTERESTINGNESS_SPREAD_WEIGHT: f64 = 0.25;
+
+/// Weight of surviving without filling the world in `interestingness`
+pub const INTERESTINGNESS_SURVIVAL_WEIGHT: f64 = 0.25;
+
+/// Scores how interesting `rule` looks, from 0 to 1, as a single objective
+/// for rule searches
+///
+/// A `size` by `size` world is seeded as in `growth_rate` and run for
+/// `generations` generations. The score adds up, with the weights of the
+/// `INTERESTINGNESS_*` constants:
+///
+/// - how much the number of cells changing each step varies over the second
+///   half of the run, as its standard deviation over its mean, capped at 1
+/// - the area of the live cells' bounding box over that of the world, at the
+///   end
+/// - 1 if some cells are still live at the end, but not all of them
+///
+/// Rules that die out, fill the world or freeze into still lifes score low.
+/// Fails with `Error::TooLarge` if the world would have more than
+/// `MAX_CELLS` cells.
+#[cfg(feature = "random")]
+pub fn interestingness(
+    rule: &Rule,
+    seed: u64,
+    size: usize,
+    generations: usize,
+) -> Result<f64, Error> {
+    let mut world: World = World::try_new(size, size, rule.clone())?;
+    world.gen_mode(SeedMode::Centered, seed);
+    let mut activity = Vec::with_capacity(generations);
+    for _ in 0..generations {
+        world.step();
+        activity.push(world.last_activity() as f64);
+    }
+    let tail = &activity[generations / 2..];
+    let variation = if tail.is_empty() {
+        0.0
+    } else {
+        let n = tail.len() as f64;
+        let mean = tail.iter().sum::<f64>() / n;
+        let variance = tail.iter().map(|a| (a - mean) * (a - mean)).sum::<f64>() / n;
+        if mean > 0.0 {
+            (variance.sqrt() / mean).min(1.0)
+        } else {
+            0.0
+        }
+    };
+    // `try_new` has already checked that this doesn't overflow.
+    let cells = (size * size) as f64;
+    let (width, height) = world.grid.normalized().0.extent();
+    let spread = if cells > 0.0 {
+        (width * height) as f64 / cells
+    } else {
+        0.0
+    };
+    let population = world.population();
+    let survival = if population > 0 && population < size * size {
+        1.0
+    } else {
+        0.0
+    };
+    Ok(INTERESTINGNESS_ACTIVITY_WEIGHT * variation
+        + INTERESTINGNESS_SPREAD_WEIGHT * spread
+        + INTERESTINGNESS_SURVIVAL_WEIGHT * survival)
+}
+
+/// Measures how a single-cell change to `seed` spreads under `rule`
+///
+/// Runs one world seeded with `seed` and another with `flip` toggled, and
+/// returns the Hamming distance between them after each of `generations`
+/// steps. Fails with `Error::Unbounded` if `seed` is unbounded and with
+/// `Error::TooLarge` if it has more than `MAX_CELLS` cells; a `flip` outside
+/// the seed's bounds changes nothing.
+pub fn sensitivity(
+    rule: &Rule,
+    seed: &Grid,
+    flip: Cell,
+    generations: usize,
+) -> Result<Vec<usize>, Error> {
+    let mut baseline: World = World::seeded(seed, rule.clone())?;
+    let mut perturbed = baseline.clone();
+    let flip = Coords::from(flip);
+    if !perturbed.grid.grid.remove(&flip) {
+        perturbed.grid.insert(&flip);
+    }
+    Ok((0..generations)
+        .map(|_| {
+            baseline.step();
+            perturbed.step();
+            baseline.grid.hamming_distance(&perturbed.grid)
+        })
+        .collect())
+}
+
+/// Steps `a` and `b` side by side for `generations` generations, checking
+/// after each that their grids match
+///
+/// # Panics
+///
+/// Panics, naming the first generation where they differ, if they ever do.
+#[cfg(any(test, feature = "testing"))]
+pub fn assert_step_equivalence(a: &mut World, b: &mut World, generations: usize) {
+    for generation in 1..=generations {
+        a.step();
+        b.step();
+        assert!(
+            a.grid == b.grid,
+            "worlds diverge at generation {}: {} and {} live cells",
+            generation,
+            a.population(),
+            b.population()
+        );
+    }
+}
+
+/// Returns how long a single generation lasts at `gps` generations per second
+///
+/// Non-positive and infinite rates mean "as fast as possible", and rates too
+/// slow for a `Duration` to hold give the longest one.
+fn frame_duration(gps: f64) -> Duration {
+    if gps > 0.0 && gps.is_finite() {
+        Duration::try_from_secs_f64(1.0 / gps).unwrap_or(Duration::MAX)
+    } else {
+        Duration::from_secs(0)
+    }
+}
+
+/// Returns the coordinate `d - 1` cells away from `c` along an axis of a grid
+/// `size` cells long, or `None` if that leaves the grid and `edge` says cells
+/// there are dead
+///
+/// Unbounded axes only have the edge at zero, which is always dead.
+fn resolve(c: usize, d: usize, size: Option<usize>, edge: EdgeMode) -> Option<usize> {
+    let neighbor = (c + d).checked_sub(1);
+    let size = match size {
+        None => return neighbor,
+        Some(0) => return None,
+        Some(size) => size,
+    };
+    match neighbor {
+        Some(n) if n < size => Some(n),
+        _ => match (edge, neighbor) {
+            (EdgeMode::Dead, _) => None,
+            (EdgeMode::Wrap, None) => Some(size - 1),
+            (EdgeMode::Wrap, Some(_)) => Some(0),
+            (EdgeMode::Reflect, None) => Some(0),
+            (EdgeMode::Reflect, Some(_)) => Some(size - 1),
+        },
+    }
+}
+
+/// Returns the character drawn for an area whose cells are live in the given
+/// fraction, from a space for none through `░`, `▒` and `▓` to `█` for all
+#[cfg(feature = "terminal")]
+fn shade(fraction: f64) -> char {
+    const SHADES: [char; 3] = ['\u{2591}', '\u{2592}', '\u{2593}'];
+    if fraction <= 0.0 {
+        ' '
+    } else if fraction >= 1.0 {
+        '\u{2588}'
+    } else {
+        SHADES[((fraction * 3.0).ceil() as usize).clamp(1, 3) - 1]
+    }
+}
+
+/// Returns `bytes` in URL-safe base64, without padding
+fn encode_base64url(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            let digit = (group >> (18 - 6 * i)) & 0x3F;
+            result.push(BASE64URL_DIGITS[digit as usize] as char);
+        }
+    }
+    result
+}
+
+/// Returns the bytes written in URL-safe base64 without padding by `s`, or
+/// `None` if it isn't valid
+fn decode_base64url(s: &str) -> Option<Vec<u8>> {
+    let digits = s
+        .bytes()
+        .map(|c| {
+            BASE64URL_DIGITS
+                .iter()
+                .position(|&d| d == c)
+                .map(|d| d as u32)
+        })
+        .collect::<Option<Vec<u32>>>()?;
+    let mut result = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, &digit)| group | digit << (18 - 6 * i));
+        let bytes = chunk.len() - 1;
+        // Leftover bits past the last byte must be zero, so every byte string
+        // has a single encoding.
+        if group & ((1 << (24 - 8 * bytes)) - 1) != 0 {
+            return None;
+        }
+        for i in 0..bytes {
+            result.push((group >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(result)
+}
+
+/// Returns the rows of `c` in the font `Grid::from_text` draws with, top
+/// first, with the leftmost pixel of each row in bit 4
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c {
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '\'' => [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '?' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
+
+/// Returns the positions along an axis `length` cells long that get a ruler
+/// tick, every `spacing` cells from the first, or none if `spacing` is 0
+#[cfg(feature = "terminal")]
+fn ticks(length: usize, spacing: usize) -> Vec<usize> {
+    if spacing == 0 {
+        return Vec::new();
+    }
+    (0..length).step_by(spacing).collect()
+}
+
+/// Returns where `coord` lands along an axis `size` cells long that repeats
+/// forever, which must not be empty
+#[cfg(feature = "terminal")]
+fn fold(coord: usize, size: usize) -> usize {
+    coord % size
+}
+
+/// Returns the number of the state of the full 3x3 neighborhood around
+/// `cell`, with dead cells past the grid's edges
+///
+/// Bit `dx + 3 * dy` is set when the cell at `(x + dx - 1, y + dy - 1)` is
+/// live, so bit 4 is the center, bits 0 to 2 the row above from left to right
+/// and bits 6 to 8 the row below. That's the index `Rule::output_for` takes.
+///
+/// # Example
+///
+/// ```
+/// let grid = hemoglobin::Grid::from(vec!["#", " #"]);
+/// assert_eq!(hemoglobin::state_at(&grid, &(0, 0)), (1 << 4) + (1 << 8));
+/// ```
+pub fn state_at(grid: &Grid, cell: &Coords) -> usize {
+    get_state(grid, cell, &MOORE, DEAD_EDGES)
+}
+
+/// Returns the cell `dx` right of and `dy` down from `cell` in `grid`, or
+/// `None` if it lies past an edge where cells are dead
+///
+/// Offsets go from -1 to 1, and past the grid's edges the edge modes decide
+/// which cell, if any, stands in.
+fn neighbor_at(
+    grid: &Grid,
+    cell: Cell,
+    dx: isize,
+    dy: isize,
+    edges: (EdgeMode, EdgeMode),
+) -> Option<Cell> {
+    if let Some(neighbor) = cell.checked_offset(dx, dy) {
+        match grid.bounds {
+            Some((w, h)) if neighbor.x >= w || neighbor.y >= h => {}
+            _ => return Some(neighbor),
+        }
+    }
+    let (x_edge, y_edge) = edges;
+    Some(Cell {
+        x: resolve(cell.x, (dx + 1) as usize, grid.x_bound(), x_edge)?,
+        y: resolve(cell.y, (dy + 1) as usize, grid.y_bound(), y_edge)?,
+    })
+}
+
+/// Returns how many of the 8 cells around `cell` are live
+fn count_neighbors(grid: &Grid, cell: &Coords, edges: (EdgeMode, EdgeMode)) -> u8 {
+    let cell = Cell::from(*cell);
+    let mut count = 0;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            if (dx, dy) == (0, 0) {
+                continue;
+            }
+            if let Some(neighbor) = neighbor_at(grid, cell, dx, dy, edges) {
+                if grid.contains(&neighbor.into()) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+fn get_state(
+    grid: &Grid,
+    cell: &Coords,
+    mask: &NeighborhoodMask,
+    edges: (EdgeMode, EdgeMode),
+) -> usize {
+    let cell = Cell::from(*cell);
+    let mut val = 0;
+    // We now build up an integer representation of the state centered at cell.
+    // We iterate over neighboring cells: dx and dy go over [-1, 0, 1] where
+    //   -1 means "left" for x or "up" for y
+    //   0 means same row (for x) or column (for y).
+    //   1 means "right" for x or "down" for y.
+    // The neighbor's bit is (dx + 1) + 3 * (dy + 1). However, if we're at an
+    // edge, the neighbor might lie off the grid. What lies beyond depends on
+    // the edge mode of each axis, which neighbor_at takes care of: it returns
+    // None when the neighbor is off the grid and dead.
+    //
+    // Neighbors left out by the mask don't get a bit at all: the remaining
+    // ones are packed into the low bits, keeping the same order, so masks
+    // yield indices into a table of 2^(number of neighbors) entries.
+    //
+    // TODO: replace "integer representation" with canonical name once we pick
+    // one.
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let position = ((dx + 1) + 3 * (dy + 1)) as usize;
+            if !mask[position] {
+                continue;
+            }
+            let live = neighbor_at(grid, cell, dx, dy, edges)
+                .is_some_and(|neighbor| grid.contains(&neighbor.into()));
+            if live {
+                val += 1 << mask[..position].iter().filter(|&&m| m).count();
+            }
+        }
+    }
+    val
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::PrimInt;
+
+    /// Returns a `width` by `height` grid with `cells` live
+    fn bounded_grid(cells: &[Coords], width: usize, height: usize) -> Grid {
+        let mut grid = Grid::new(Some((width, height)));
+        for cell in cells {
+            grid.insert(cell);
+        }
+        grid
+    }
+
+    const EXPECTED_1082_BITS: [bool; 16] = [
+        false,
+        true,
+        false,
+        true,
+        false,
+        false,
+        false,
+        false,
+        true,
+        true,
+        true,
+        false,
+        false,
+        false,
+        false,
+        false,
+    ];
+    // 1802 = 10 + 7*(2^8), so writing in little endian byte order but writing
+    // the bits within each byte with MSB on the left, we have
+    // [00001010][00000111].
+    // As a BitVec, we want 01010000 11100000.
+
+    #[test]
+    fn test_bitvec_order() {
+        // Consider a two-byte number where the firt byte's value is 10 and
+        // the second byte's value is 7. Converting to a little endian byte
+        // array should make the 0th byte 10 and the 1th byte 7.
+        let ten_seven = BigUint::from(10 + 7 * (2.pow(8)) as u32);
+        let bytes = ten_seven.to_bytes_le();
+        assert_eq!(bytes[0], 10);
+        assert_eq!(bytes[1], 7);
+        // Now check what happens when we convert this to a BitVec. The bytes
+        // are in little endian order, but the bits within each byte are big
+        // endian:
+        // [00001010][00000111]
+        let bits = BitVec::from_bytes(&bytes);
+        let expected = vec![
+            // 0th byte representing 10
+            false,
+            false,
+            false,
+            false,
+            true,
+            false,
+            true,
+            false,
+            // 1st byte representing 7
+            false,
+            false,
+            false,
+            false,
+            false,
+            true,
+            true,
+            true,
+        ];
+        for i in 0..16 {
+            assert_eq!(bits[i], expected[i]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_rule_bit_order() {
+        // Bit i of the rule number, worked out one shift at a time, must be
+        // the output for state i however the rule was built.
+        let reference = |x: &BigUint| -> Vec<bool> {
+            (0..512)
+                .map(|i| (x >> i) % BigUint::from(2u32) == BigUint::from(1u32))
+                .collect()
+        };
+        let check = |x: BigUint| {
+            let from_number = Rule::from(x.clone());
+            let from_string = Rule::from(x.to_str_radix(10));
+            assert_eq!(from_number.bin, from_string.bin);
+            assert!(from_number.bin.iter().eq(reference(&x).into_iter()));
+        };
+
+        // 272 = 2^4 + 2^8: only the center and the bottom right corner.
+        let by_hand = Rule::from("272".to_string());
+        let live: Vec<usize> = (0..512).filter(|&i| by_hand.bin[i]).collect();
+        assert_eq!(live, vec![4, 8]);
+        check(BigUint::from(272u32));
+        check(gen_conway_dec());
+
+        let mut rng = StdRng::seed_from_u64(134);
+        for _ in 0..64 {
+            let bytes: Vec<u8> = (0..64).map(|_| rng.gen()).collect();
+            check(BigUint::from_bytes_le(&bytes));
+        }
+    }
+
+    #[test]
+    fn test_one_hot_bit_order() {
+        // A neighborhood with only the cell at position p live, p being
+        // dx + 3 * dy from the top left corner, is state 1 << p, and only the
+        // rule numbered 2^(1 << p) brings the center to life from it.
+        for p in 0..9 {
+            let mut world = World::new(3, 3, Rule::from(BigUint::from(0u32)));
+            world.grid.insert(&(p % 3, p / 3));
+            assert_eq!(get_state(&world.grid, &(1, 1), &MOORE, DEAD_EDGES), 1 << p);
+            for q in 0..9 {
+                world.set_rule(Rule::from(BigUint::from(1u32) << (1usize << q)));
+                assert_eq!(world.next_state(&(1, 1)), p == q, "cell {} under rule {}", p, q);
+            }
+        }
+    }
+
+    fn gen_conway_dec() -> BigUint {
+        let mut kode = BigUint::from(0u32);
+        for state in 0..512 {
+            let mut bit_count = 0usize;
+            let current_state = (state >> 4) % 2;
+            for bit_offset in [0, 1, 2, 3, 5, 6, 7, 8].iter() {
+                bit_count += (state >> bit_offset) & 1usize;
+            }
+            let result = BigUint::from(match bit_count {
+                2 => current_state,
+                3 => 1,
+                _ => 0,
+            });
+            kode += result << state;
+        }
+        kode
+    }
+
+    #[test]
+    fn test_gen_conway_dec() {
+        let expected = "476348294852520375132009738840824718882889556\
+                        423255282629108876378472743729817205343700177\
+                        683429960362194923168607044012736510546282236\
+                        08960"
+            .parse::<BigUint>()
+            .unwrap();
+        assert_eq!(expected, gen_conway_dec());
+    }
+
+    #[test]
+    fn test_grid_from_string() {
+        let grid = Grid::from(vec!["   ", "   "]);
+        let mut expected = Grid::new(None);
+        assert_eq!(grid, expected);
+
+        let grid = Grid::from(vec!["#  ", "   "]);
+        expected.insert(&(0, 0));
+        assert_eq!(grid, expected);
+
+        let grid = Grid::from(vec!["#  ", " # "]);
+        expected.insert(&(1, 1));
+        assert_eq!(grid, expected);
+    }
+
+    #[test]
+    fn test_rule_dec_str_to_rule() {
+        let rule = Rule::from("1802".to_string());
+        for (i, &expected) in EXPECTED_1082_BITS.iter().enumerate() {
+            assert_eq!(rule.bin[i], expected);
+        }
+    }
+
+    #[test]
+    fn test_rule_from_bigint() {
+        let rule = Rule::from(BigUint::from(1802u32));
+        for (i, &expected) in EXPECTED_1082_BITS.iter().enumerate() {
+            assert_eq!(rule.bin[i], expected);
+        }
+    }
+
+    #[test]
+    fn test_neighborhood_mask() {
+        let rule = Rule::with_mask(BigUint::from(0u32), VON_NEUMANN);
+        assert_eq!(rule.bin.len(), 32);
+
+        // Von Neumann states number the neighbors north, west, center, east,
+        // south from the lowest bit up.
+        let grid = Grid::from(vec!["###", "###", "###"]);
+        assert_eq!(get_state(&grid, &(1, 1), &VON_NEUMANN, DEAD_EDGES), 0b11111);
+        let grid = Grid::from(vec!["# #", "   ", "# #"]);
+        assert_eq!(get_state(&grid, &(1, 1), &VON_NEUMANN, DEAD_EDGES), 0);
+        let grid = Grid::from(vec!["## ", " # ", "  #"]);
+        assert_eq!(get_state(&grid, &(1, 1), &VON_NEUMANN, DEAD_EDGES), 0b00101);
+        let grid = Grid::from(vec!["   ", "  #", " # "]);
+        assert_eq!(get_state(&grid, &(1, 1), &VON_NEUMANN, DEAD_EDGES), 0b11000);
+    }
+
+    #[test]
+    fn test_set_rule_other_neighborhood() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        // The von Neumann identity: the center is the third cell the mask
+        // selects, so it's bit 2 of the packed state.
+        let mut identity = BigUint::from(0u32);
+        for state in (0..32).filter(|state| state & 0b100 != 0) {
+            identity += BigUint::from(1u32) << state;
+        }
+        world.set_rule(Rule::with_mask(identity, VON_NEUMANN));
+        let before = world.grid.clone();
+        world.step();
+        assert_eq!(world.grid, before);
+    }
+
+    #[test]
+    fn test_stack() {
+        let mut left = World::new(5, 5, Rule::from(gen_conway_dec()));
+        left.grid.insert(&(1, 1));
+        let mut right = World::new(5, 5, Rule::from(gen_conway_dec()));
+        right.grid.insert(&(2, 3));
+
+        let wide = left.hstack(&right).unwrap();
+        let mut expected = Grid::new(Some((10, 5)));
+        expected.insert(&(1, 1));
+        expected.insert(&(7, 3));
+        assert_eq!(wide.grid, expected);
+
+        let tall = left.vstack(&right).unwrap();
+        let mut expected = Grid::new(Some((5, 10)));
+        expected.insert(&(1, 1));
+        expected.insert(&(2, 8));
+        assert_eq!(tall.grid, expected);
+
+        let short = World::new(5, 4, Rule::from(gen_conway_dec()));
+        assert_eq!(
+            left.hstack(&short).err(),
+            Some(WorldError::SizeMismatch {
+                expected: 5,
+                found: 4
+            })
+        );
+        let other_rule = World::new(5, 5, Rule::from(BigUint::from(0u32)));
+        assert_eq!(left.vstack(&other_rule).err(), Some(WorldError::RuleMismatch));
+
+        let half = World::new(MAX_CELLS / 2 + 1, 1, Rule::from(gen_conway_dec()));
+        assert_eq!(
+            half.hstack(&half).err(),
+            Some(WorldError::TooLarge {
+                width: MAX_CELLS + 2,
+                height: 1,
+                limit: MAX_CELLS
+            })
+        );
+        let endless = World::new(usize::MAX, 0, Rule::from(gen_conway_dec()));
+        assert!(matches!(endless.hstack(&endless), Err(WorldError::TooLarge { .. })));
+        let column = World::new(1, MAX_CELLS, Rule::from(gen_conway_dec()));
+        assert!(matches!(column.vstack(&column), Err(WorldError::TooLarge { .. })));
+    }
+
+    #[test]
+    fn test_set_rule() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        world.step();
+        let vertical = Grid::from(vec!["     ", "  #  ", "  #  ", "  #  "]);
+        assert_eq!(world.grid.grid, vertical.grid);
+
+        // Under the identity rule a cell's next state is its current state,
+        // so the blinker must stop oscillating.
+        let mut identity = BigUint::from(0u32);
+        for state in 0..512 {
+            if (state >> 4) & 1 == 1 {
+                identity += BigUint::from(1u32) << state;
+            }
+        }
+        world.set_rule(Rule::from(identity.clone()));
+        assert_eq!(world.rule().to_string(), identity.to_string());
+        world.step();
+        world.step();
+        assert_eq!(world.grid.grid, vertical.grid);
+    }
+
+    #[test]
+    fn test_frame_duration() {
+        assert_eq!(frame_duration(1.0), Duration::from_secs(1));
+        assert_eq!(frame_duration(4.0), Duration::from_millis(250));
+        assert_eq!(frame_duration(0.0), Duration::from_secs(0));
+        assert_eq!(frame_duration(-3.0), Duration::from_secs(0));
+        assert_eq!(frame_duration(f64::INFINITY), Duration::from_secs(0));
+        assert_eq!(frame_duration(1e-300), Duration::MAX);
+        assert_eq!(frame_duration(f64::MIN_POSITIVE), Duration::MAX);
+    }
+
+    #[test]
+    fn test_run_realtime() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        let mut frames = Vec::new();
+        world.run_realtime(1e9, 4, |grid| frames.push(grid.grid.len()));
+        assert_eq!(frames, vec![3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn test_adjacent_rules() {
+        let rule = Rule::from(gen_conway_dec());
+        let adjacent: Vec<Rule> = rule.adjacent_rules().collect();
+        assert_eq!(adjacent.len(), 512);
+        for (state, other) in adjacent.iter().enumerate() {
+            assert_ne!(other.bin[state], rule.bin[state]);
+            assert_eq!(Rule::from(other.to_string()).bin, other.bin);
+        }
+    }
+
+    /// Returns how many generations `world` stays populated, up to `cap`
+    #[cfg(feature = "random")]
+    fn lifetime(mut world: World, cap: usize) -> usize {
+        for generation in 0..cap {
+            if world.grid.grid.is_empty() {
+                return generation;
+            }
+            world.step();
+        }
+        cap
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_search_rule() {
+        let mut seed = Grid::new(Some((8, 8)));
+        for &cell in [(3, 3), (4, 3), (3, 4), (5, 5)].iter() {
+            seed.insert(&cell);
+        }
+        let population = |world: &World| world.grid.grid.len() as f64;
+        // With no iterations the search hands back its random starting point.
+        let start = search_rule(&seed, population, 0, 0).unwrap();
+        let found = search_rule(&seed, population, 50, 0).unwrap();
+        let cap = 2 * SEARCH_GENERATIONS;
+        assert!(
+            lifetime(World::seeded(&seed, found).unwrap(), cap)
+                > lifetime(World::seeded(&seed, start).unwrap(), cap)
+        );
+    }
+
+    #[test]
+    fn test_life106_round_trip() {
+        let grid = Grid::from_life106("#Life 1.06\n-1 -2\n0 -1\n-2 0\n").unwrap();
+        assert_eq!(grid, Grid::from(vec![" # ", "  #", "#  "]));
+        let exported = grid.to_life106();
+        assert_eq!(exported, "#Life 1.06\n1 0\n2 1\n0 2\n");
+        assert_eq!(Grid::from_life106(&exported).unwrap(), grid);
+
+        assert!(Grid::from_life106("0 0\n").is_err());
+        assert!(Grid::from_life106("#Life 1.06\n0\n").is_err());
+        let far_apart = format!("#Life 1.06\n{} 0\n{} 0\n", i64::MIN, i64::MAX);
+        assert!(matches!(
+            Grid::from_life106(&far_apart),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_lambda() {
+        assert_eq!(Rule::from(BigUint::from(0u32)).lambda(), 0.0);
+        let all_alive = (BigUint::from(1u32) << 512) - BigUint::from(1u32);
+        assert_eq!(Rule::from(all_alive).lambda(), 1.0);
+        // 56 ways to be born with three neighbors plus 28 + 56 ways to survive
+        // with two or three: 140 / 512.
+        assert_eq!(Rule::from(gen_conway_dec()).lambda(), 0.2734375);
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_empty_worlds() {
+        for &(width, height) in [(0, 0), (1, 0)].iter() {
+            let mut world = World::new(width, height, Rule::from(BigUint::from(1u32)));
+            world.grid.insert(&(0, 0));
+            world.gen();
+            world.step();
+            assert_eq!(world.population(), 0);
+            assert_eq!(world.density(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_to_pbm() {
+        let path = std::env::temp_dir().join("hemoglobin-test-to-pbm.pbm");
+        let mut grid = Grid::new(Some((3, 2)));
+        grid.insert(&(0, 0));
+        grid.insert(&(2, 1));
+        grid.to_pbm(&path).unwrap();
+        let pbm = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut tokens = pbm.split_whitespace();
+        assert_eq!(tokens.next(), Some("P1"));
+        assert_eq!(tokens.next(), Some("3"));
+        assert_eq!(tokens.next(), Some("2"));
+        let data: Vec<&str> = tokens.collect();
+        assert_eq!(data, vec!["1", "0", "0", "0", "0", "1"]);
+    }
+
+    #[test]
+    fn test_advance() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        for generation in 1..3 {
+            let report = world.advance();
+            assert_eq!(
+                report,
+                StepReport {
+                    generation,
+                    population: 3,
+                    activity: 4,
+                    changed: true,
+                }
+            );
+        }
+        assert_eq!(world.generation(), 2);
+    }
+
+    #[test]
+    fn test_activity_log() {
+        let mut block = World::new(6, 6, Rule::from(gen_conway_dec()));
+        for &cell in [(2, 2), (3, 2), (2, 3), (3, 3)].iter() {
+            block.grid.insert(&cell);
+        }
+        assert_eq!(block.activity_log(5), vec![0; 5]);
+
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
+        }
+        assert_eq!(blinker.activity_log(5), vec![4; 5]);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut grid = Grid::new(None);
+        for x in 0..10_000 {
+            grid.insert(&(x, 0));
+        }
+        grid.grid.retain(|&(x, _)| x < 10);
+        let capacity = grid.capacity();
+        grid.shrink_to_fit();
+        assert!(grid.capacity() < capacity);
+        assert_eq!(grid.population(), 10);
+
+        // Fill both of the world's grids so they grow, then leave a blinker.
+        let mut world = World::new(100, 100, Rule::from(gen_conway_dec()));
+        let corners = (Cell { x: 0, y: 0 }, Cell { x: 99, y: 99 });
+        world.fill_rect(corners.0, corners.1, true);
+        world.step();
+        world.fill_rect(corners.0, corners.1, true);
+        world.fill_rect(corners.0, corners.1, false);
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        let capacity = world.grid.capacity().min(world.swap_grid.capacity());
+        world.set_auto_shrink(true);
+        world.step();
+        assert!(world.grid.capacity() < capacity);
+        assert_eq!(world.population(), 3);
+    }
+
+    #[test]
+    fn test_rule_from_bs() {
+        assert_eq!(Rule::from_bs("B3/S23").unwrap(), Rule::from(gen_conway_dec()));
+        assert_eq!(Rule::from_bs("b3/s23").unwrap(), Rule::from_bs("B3/S23").unwrap());
+        assert_eq!(Rule::from_bs("B/S").unwrap(), Rule::from(BigUint::from(0u32)));
+        assert!(Rule::from_bs("B3").is_err());
+        assert!(Rule::from_bs("B9/S23").is_err());
+        assert!(Rule::from_bs("S23/B3").is_err());
+    }
+
+    #[test]
+    fn test_rule_named() {
+        assert_eq!(Rule::named("HighLife"), Some(Rule::from_bs("B36/S23").unwrap()));
+        assert_eq!(Rule::named("conway"), Some(Rule::from(gen_conway_dec())));
+        assert_eq!(Rule::named("Day & Night"), Rule::from_bs("B3678/S34678").ok());
+        assert_eq!(Rule::named("Brian's Brain"), None);
+    }
+
+    #[test]
+    fn test_response_table() {
+        let conway = Rule::from(gen_conway_dec());
+        let mut birth = [false; 9];
+        birth[3] = true;
+        let mut survival = [false; 9];
+        survival[2] = true;
+        survival[3] = true;
+        assert_eq!(conway.response_table(), [birth, survival]);
+        assert!(conway.is_outer_totalistic());
+        // A lone live neighbor in the bottom right corner now gives birth, but
+        // one anywhere else still doesn't.
+        assert!(!conway.flipped(1 << 8).is_outer_totalistic());
+    }
+
+    #[test]
+    fn test_clone_world() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        let grid = world.grid.clone();
+        let mut clone = world.clone();
+        clone.step();
+        clone.step();
+        clone.step();
+        assert_eq!(world.grid, grid);
+        assert_eq!(world.generation(), 0);
+        assert_ne!(clone.grid, grid);
+        assert_eq!(clone.generation(), 3);
+    }
+
+    #[test]
+    fn test_detect_spaceship() {
+        let glider = Grid::from(vec![" # ", "  #", "###"]);
+        let mut world = World::new(10, 10, Rule::from(gen_conway_dec()));
+        for cell in glider.grid.iter() {
+            world.grid.insert(cell);
+        }
+        assert_eq!(
+            world.detect_spaceship(8),
+            Some(Spaceship {
+                period: 4,
+                displacement: (1, 1),
+            })
+        );
+
+        let mut block = World::new(6, 6, Rule::from(gen_conway_dec()));
+        for &cell in [(2, 2), (3, 2), (2, 3), (3, 3)].iter() {
+            block.grid.insert(&cell);
+        }
+        assert_eq!(block.detect_spaceship(8), None);
+
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
+        }
+        assert_eq!(blinker.detect_spaceship(8), None);
+    }
+
+    #[test]
+    fn test_try_insert() {
+        let mut grid = Grid::new(Some((3, 2)));
+        assert_eq!(grid.try_insert((2, 1)), Ok(()));
+        assert_eq!(
+            grid.try_insert((1, 2)),
+            Err(OutOfBounds {
+                cell: (1, 2),
+                bounds: (3, 2),
+            })
+        );
+        assert_eq!(grid.population(), 1);
+        assert_eq!(Grid::new(None).try_insert((7, 9)), Ok(()));
+    }
+
+    #[cfg(feature = "gif")]
+    fn count_gif_frames(path: &std::path::Path) -> usize {
+        let file = File::open(path).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(file).unwrap();
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        frames
+    }
+
+    #[test]
+    #[cfg(feature = "gif")]
+    fn test_render_gif() {
+        let path = std::env::temp_dir().join("hemoglobin-test-render-gif.gif");
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
+        }
+        blinker.render_gif(&path, 4, 3, 100).unwrap();
+        assert_eq!(count_gif_frames(&path), 5);
+
+        // A lone cell dies straight away, so only the first two frames exist.
+        let mut lone = World::new(5, 5, Rule::from(gen_conway_dec()));
+        lone.grid.insert(&(2, 2));
+        lone.render_gif(&path, 4, 3, 100).unwrap();
+        assert_eq!(count_gif_frames(&path), 2);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut huge = World::new(70_000, 1, Rule::from(gen_conway_dec()));
+        match huge.render_gif(&path, 1, 1, 100) {
+            Err(GifError::TooLarge) => {}
+            other => panic!("expected GifError::TooLarge, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_perimeter() {
+        assert_eq!(Grid::from(vec!["   ", " # "]).perimeter(), 4);
+        assert_eq!(Grid::from(vec!["#"]).perimeter(), 4);
+        assert_eq!(Grid::from(vec!["    ", " ## ", " ## "]).perimeter(), 8);
+        assert_eq!(Grid::from(vec!["###"]).perimeter(), 8);
+    }
+
+    #[test]
+    fn test_components() {
+        let grid = Grid::from(vec!["       #", "###    #", "       #"]);
+        let components = grid.components();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0], Grid::from(vec!["       #", "       #", "       #"]));
+        assert_eq!(components[1], Grid::from(vec!["        ", "###"]));
+
+        // Diagonal contact joins cells into one component.
+        let grid = Grid::from(vec!["#  ", " # ", "  #"]);
+        assert_eq!(grid.components().len(), 1);
+        assert!(Grid::new(None).components().is_empty());
+    }
+
+    #[test]
+    fn test_differing_states() {
+        let conway = Rule::from(gen_conway_dec());
+        assert!(conway.differing_states(&conway).is_empty());
+        let mutant = conway.adjacent_rules().nth(42).unwrap();
+        assert_eq!(conway.differing_states(&mutant), vec![42]);
+        let highlife = Rule::named("HighLife").unwrap();
+        // Birth on six neighbors: any C(8, 6) = 28 of them with the center dead.
+        assert_eq!(conway.differing_states(&highlife).len(), 28);
+    }
+
+    #[test]
+    fn test_get_state_edges() {
+        let grid = bounded_grid(&[(0, 0), (2, 2)], 3, 3);
+        let wrap = (EdgeMode::Wrap, EdgeMode::Wrap);
+        // The corner cells are neighbors across both edges of a torus.
+        assert_eq!(get_state(&grid, &(0, 0), &MOORE, wrap), 16 + 1);
+        assert_eq!(get_state(&grid, &(0, 0), &MOORE, DEAD_EDGES), 16);
+        // Reflected, the top left cell is also its own left, top and top left
+        // neighbor.
+        let reflect = (EdgeMode::Reflect, EdgeMode::Reflect);
+        assert_eq!(get_state(&grid, &(0, 0), &MOORE, reflect), 16 + 8 + 2 + 1);
+        let cylinder = (EdgeMode::Wrap, EdgeMode::Dead);
+        assert_eq!(get_state(&grid, &(2, 0), &MOORE, cylinder), 32);
+    }
+
+    #[test]
+    fn test_cylinder() {
+        // A glider heading up and right on a cylinder 6 wide and 8 tall.
+        let glider = [(3, 5), (4, 5), (5, 5), (5, 6), (4, 7)];
+        let mut world = World::new(6, 8, Rule::from(gen_conway_dec()));
+        world.set_edges(EdgeMode::Wrap, EdgeMode::Dead);
+        for cell in glider.iter() {
+            world.grid.insert(cell);
+        }
+        // Every four generations it moves one cell up and one right, wrapping
+        // around from the right edge to the left one on the way.
+        for period in 1..6 {
+            for _ in 0..4 {
+                world.step();
+            }
+            let shifted: Vec<Coords> = glider
+                .iter()
+                .map(|&(x, y)| ((x + period) % 6, y - period))
+                .collect();
+            assert_eq!(world.grid, bounded_grid(&shifted, 6, 8));
+        }
+        // Having reached the top edge it crashes into it and leaves a block.
+        for _ in 0..3 {
+            world.step();
+        }
+        let block = bounded_grid(&[(3, 0), (4, 0), (3, 1), (4, 1)], 6, 8);
+        assert_eq!(world.grid, block);
+        world.step();
+        assert_eq!(world.grid, block);
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
+        }
+        let horizontal = blinker.grid.clone();
+        let mut vertical = blinker.clone();
+        vertical.step();
+
+        blinker.fast_forward(1000);
+        assert_eq!(blinker.generation(), 1000);
+        assert_eq!(blinker.grid, horizontal);
+        blinker.fast_forward(2001);
+        assert_eq!(blinker.generation(), 2001);
+        assert_eq!(blinker.grid, vertical.grid);
+        blinker.fast_forward(10);
+        assert_eq!(blinker.generation(), 2001);
+
+        // A glider never repeats in place, so it's stepped all the way.
+        let mut glider = World::new(10, 10, Rule::from(gen_conway_dec()));
+        for &cell in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+            glider.grid.insert(&cell);
+        }
+        let mut stepped = glider.clone();
+        glider.fast_forward(8);
+        for _ in 0..8 {
+            stepped.step();
+        }
+        assert_eq!(glider.generation(), 8);
+        assert_eq!(glider.grid, stepped.grid);
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_outer_totalistic_rule() {
+        let conway = OuterTotalisticRule::from_bs("B3/S23").unwrap();
+        assert_eq!(Rule::from(&conway), Rule::from(gen_conway_dec()));
+
+        let mut table = World::new(16, 16, Rule::from(gen_conway_dec()));
+        table.grid.gen().unwrap();
+        for &edges in [DEAD_EDGES, (EdgeMode::Wrap, EdgeMode::Reflect)].iter() {
+            table.edges = edges;
+            let mut counted = table.clone();
+            for _ in 0..20 {
+                table.step();
+                counted.step_with(&conway);
+                assert_eq!(counted.grid, table.grid);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_random_rule() {
+        let rule = Rule::random(&mut StdRng::seed_from_u64(1802));
+        assert_eq!(rule, Rule::random(&mut StdRng::seed_from_u64(1802)));
+        assert_ne!(rule, Rule::random(&mut StdRng::seed_from_u64(1803)));
+        assert_eq!(Rule::from(rule.to_string()), rule);
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_mutate_and_crossover() {
+        let mut rng = StdRng::seed_from_u64(188);
+        let conway = Rule::from(gen_conway_dec());
+        assert_eq!(conway.mutate(0.0, &mut rng), conway);
+        let inverted = conway.mutate(1.0, &mut rng);
+        assert!((0..512).all(|state| inverted.bin[state] != conway.bin[state]));
+        assert_eq!(Rule::crossover(&conway, &conway, &mut rng), conway);
+        let child = Rule::crossover(&conway, &inverted, &mut rng);
+        assert_ne!(child, conway);
+        assert_ne!(child, inverted);
+    }
+
+    #[test]
+    fn test_from_str_bounded() {
+        let glider = [" # ", "  #", "###"];
+        let grid = Grid::from_str_bounded(&glider, 5, 5);
+        let expected = bounded_grid(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], 5, 5);
+        assert_eq!(grid, expected);
+
+        let clipped = Grid::from_str_bounded(&glider, 2, 2);
+        assert_eq!(clipped, bounded_grid(&[(1, 0)], 2, 2));
+    }
+
+    #[test]
+    fn test_neighbor_count_map() {
+        let mut blinker = World::new(5, 3, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 1));
+        }
+        assert_eq!(
+            blinker.neighbor_count_map(),
+            vec![
+                vec![1, 2, 3, 2, 1],
+                vec![1, 1, 2, 1, 1],
+                vec![1, 2, 3, 2, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        match "18o2".parse::<Rule>() {
+            Err(Error::Parse(_)) => {}
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        let too_big = (BigUint::from(1u32) << 512).to_string();
+        assert!(matches!(too_big.parse::<Rule>(), Err(Error::Parse(_))));
+        assert_eq!("1802".parse::<Rule>().unwrap(), Rule::from(BigUint::from(1802u32)));
+        assert!(matches!(Rule::from_bs("B3S23"), Err(Error::Parse(_))));
+        assert!(matches!(Grid::from_life106("1 2"), Err(Error::Parse(_))));
+
+        let mut grid = Grid::new(Some((2, 2)));
+        let inserted: Result<(), Error> = grid.try_insert((2, 0)).map_err(Error::from);
+        assert!(matches!(
+            inserted,
+            Err(Error::OutOfBounds(OutOfBounds {
+                cell: (2, 0),
+                bounds: (2, 2),
+            }))
+        ));
+
+        let conway = Rule::from(gen_conway_dec());
+        assert!(matches!(
+            Grid::new(None).is_garden_of_eden(&conway),
+            Err(Error::Unbounded)
+        ));
+        #[cfg(feature = "random")]
+        {
+            assert!(matches!(Grid::new(None).gen(), Err(Error::Unbounded)));
+            let seed = Grid::from(vec!["##", "##"]);
+            let fitness = |world: &World| world.population() as f64;
+            assert!(matches!(search_rule(&seed, fitness, 1, 0), Err(Error::Unbounded)));
+        }
+
+        let path = std::env::temp_dir().join("hemoglobin-missing-dir").join("grid.pbm");
+        assert!(matches!(grid.to_pbm(&path), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn test_is_alive() {
+        let grid = Grid::from(vec!["# #", " # "]);
+        for &cell in [(0, 0), (2, 0), (1, 1)].iter() {
+            assert!(grid.is_alive(cell));
+            assert!(grid.contains(&cell));
+        }
+        for &cell in [(1, 0), (0, 1), (2, 1), (5, 5)].iter() {
+            assert!(!grid.is_alive(cell));
+        }
+    }
+
+    #[test]
+    fn test_next_grid() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        let before = world.clone();
+        let next = world.next_grid();
+        assert_eq!(world.grid, before.grid);
+        assert_eq!(world.swap_grid, before.swap_grid);
+        assert_eq!(world.generation(), 0);
+
+        world.step();
+        assert_eq!(next, world.grid);
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_gen_mode() {
+        let (clusters, radius) = (3, 4);
+        let mut world = World::new(40, 30, Rule::from(gen_conway_dec()));
+        world.gen_mode(SeedMode::Clustered { clusters, radius }, 7);
+        assert!(world.population() > 0);
+        let centers = cluster_centers(&mut StdRng::seed_from_u64(7), (40, 30), clusters);
+        for &cell in world.grid.grid.iter() {
+            assert!(centers.iter().any(|&c| within_radius(cell, c, radius)));
+        }
+
+        let mut again = World::new(40, 30, Rule::from(gen_conway_dec()));
+        again.gen_mode(SeedMode::Clustered { clusters, radius }, 7);
+        assert_eq!(again.grid, world.grid);
+
+        world.gen_mode(SeedMode::Centered, 7);
+        assert!(world.grid.grid.iter().all(|&(x, y)| (10..30).contains(&x) && (7..23).contains(&y)));
+        assert!(Grid::new(None).gen_mode(SeedMode::Uniform, 7).is_err());
+    }
+
+    #[test]
+    fn test_write_rle() {
+        let glider = Grid::from(vec!["", "  #", "   #", " ###"]);
+        let mut out = Vec::new();
+        glider.write_rle(&mut out, Some(&Rule::from(gen_conway_dec()))).unwrap();
+        let rle = String::from_utf8(out).unwrap();
+        assert!(rle.starts_with(&format!("x = 3, y = 3, rule = {}\n", gen_conway_dec())));
+        assert!(rle.ends_with("\nbo$2bo$3o!\n"));
+        assert_eq!(Grid::from_rle(&rle).unwrap(), glider.normalized().0);
+
+        // A checkerboard takes far more than one line.
+        let mut board = Grid::new(None);
+        for x in 0..100 {
+            for y in 0..20 {
+                if (x + y) % 2 == 0 {
+                    board.insert(&(x, y));
+                }
+            }
+        }
+        let rle = board.to_rle(None);
+        assert!(rle.lines().all(|line| line.len() <= RLE_LINE_LEN));
+        assert!(rle.lines().count() > 2);
+        assert_eq!(Grid::from_rle(&rle).unwrap(), board);
+
+        assert!(Grid::from_rle("bo$2bo$3o!").is_err());
+        assert!(Grid::from_rle("x = 1, y = 1\no").is_err());
+
+        // Runs too long for a grid's coordinates fail instead of overflowing.
+        let oversized = [
+            "x = 1, y = 1\n99999999999999999999999o!".to_string(),
+            format!("x = 1, y = 1\n{}b2o!", usize::MAX),
+            format!("x = 1, y = 1\n{}bo{}b!", usize::MAX, usize::MAX),
+            format!("x = 1, y = 1\n{}$2$o!", usize::MAX),
+        ];
+        for rle in oversized.iter() {
+            assert!(
+                matches!(Grid::from_rle(rle), Err(Error::Parse(_))),
+                "{}",
+                rle
+            );
+        }
+
+        // So do patterns with more live cells, or spread wider, than a world
+        // can hold.
+        let crowded = [
+            "x = 1, y = 1\n99999999999o!".to_string(),
+            format!("x = 1, y = 1\no{}$o!", MAX_CELLS),
+            format!("x = 1, y = 1\n{}bo$o!", MAX_CELLS / 2),
+        ];
+        for rle in crowded.iter() {
+            assert!(
+                matches!(Grid::from_rle(rle), Err(Error::TooLarge { limit: MAX_CELLS, .. })),
+                "{}",
+                rle
+            );
+        }
+        assert_eq!(
+            Grid::from_rle(&format!("x = 1, y = 1\n{}bo!", MAX_CELLS - 1))
+                .unwrap()
+                .extent(),
+            (MAX_CELLS, 1)
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        world.set_edges(EdgeMode::Wrap, EdgeMode::Wrap);
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+        }
+        world.step();
+        world.reset();
+        assert_eq!(world.population(), 0);
+        assert_eq!(world.generation(), 0);
+        assert_eq!(world.last_activity(), 0);
+        assert_eq!(world.swap_grid.population(), 0);
+        assert_eq!(world.dimensions(), (5, 5));
+        assert_eq!(world.edges(), (EdgeMode::Wrap, EdgeMode::Wrap));
+        assert_eq!(world.rule(), &Rule::from(gen_conway_dec()));
+    }
+
+    #[test]
+    fn test_pattern_meta() {
+        let rle = "#N Glider\n#O Richard K. Guy\n#C The smallest spaceship.\n#C Found in 1969.\n\
+                   x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+        let pattern = Pattern::from_rle(rle).unwrap();
+        assert_eq!(pattern.meta.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.meta.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.meta.comments, vec!["The smallest spaceship.", "Found in 1969."]);
+        assert_eq!(pattern.rule, Some(Rule::from(gen_conway_dec())));
+        assert_eq!(pattern.grid, Grid::from(vec![" # ", "  #", "###"]));
+
+        let cells = "!Name: Blinker\n!A period 2 oscillator.\n...\nOOO\n";
+        let pattern = Pattern::from_cells(cells).unwrap();
+        assert_eq!(pattern.meta.name.as_deref(), Some("Blinker"));
+        assert_eq!(pattern.meta.author, None);
+        assert_eq!(pattern.meta.comments, vec!["A period 2 oscillator."]);
+        assert_eq!(pattern.rule, None);
+        assert_eq!(pattern.grid, Grid::from(vec!["", "###"]));
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_step_fn() {
+        let conway = |alive, count| count == 3 || (alive && count == 2);
+        let mut world = World::new(12, 12, Rule::from(gen_conway_dec()));
+        world.gen_mode(SeedMode::Centered, 138);
+        let mut by_fn = world.clone();
+        for _ in 0..8 {
+            world.step();
+            by_fn.step_fn(conway);
+            assert_eq!(by_fn.grid, world.grid);
+        }
+        assert_eq!(by_fn.generation(), 8);
+    }
+
+    #[test]
+    fn test_rows() {
+        let grid = Grid::from_str_bounded(&["# ", " #", "##"], 2, 3);
+        let rows: Vec<Vec<bool>> = grid.rows().collect();
+        assert_eq!(
+            rows,
+            vec![vec![true, false], vec![false, true], vec![true, true]]
+        );
+
+        let unbounded = Grid::from(vec!["", " #"]);
+        let rows: Vec<Vec<bool>> = unbounded.rows().collect();
+        assert_eq!(rows, vec![vec![false, false], vec![false, true]]);
+        assert_eq!(Grid::new(None).rows().count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "terminal")]
+    fn test_fold() {
+        assert_eq!(fold(0, 5), 0);
+        assert_eq!(fold(4, 5), 4);
+        assert_eq!(fold(5, 5), 0);
+        assert_eq!(fold(9, 5), 4);
+        assert_eq!(fold(12, 5), 2);
+        assert_eq!(fold(7, 1), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_growth_rate() {
+        let seeds = growth_rate(&Rule::named("Seeds").unwrap(), 120, 24, 141).unwrap();
+        let conway = growth_rate(&Rule::from(gen_conway_dec()), 120, 24, 141).unwrap();
+        assert!(seeds > 0.0);
+        assert!(seeds > conway);
+        assert_eq!(growth_rate(&Rule::from(gen_conway_dec()), 120, 1, 141).unwrap(), 0.0);
+        assert!(matches!(
+            growth_rate(&Rule::from(gen_conway_dec()), usize::MAX, 1, 141),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_load_overflow() {
+        let pattern = Grid::from(vec!["#    ", "     ", "  # #"]);
+
+        let mut world = World::new(4, 4, Rule::from(gen_conway_dec()));
+        world.load(&pattern, OverflowPolicy::Clip).unwrap();
+        assert_eq!(world.dimensions(), (4, 4));
+        assert_eq!(world.grid, bounded_grid(&[(0, 0), (2, 2)], 4, 4));
+
+        let mut world = World::new(4, 4, Rule::from(gen_conway_dec()));
+        world.grid.insert(&(1, 1));
+        match world.load(&pattern, OverflowPolicy::Error) {
+            Err(Error::OutOfBounds(e)) => {
+                assert_eq!(e.cell, (4, 2));
+                assert_eq!(e.bounds, (4, 4));
+            }
+            other => panic!("expected OutOfBounds, got {:?}", other),
+        }
+        assert_eq!(world.grid, bounded_grid(&[(1, 1)], 4, 4));
+
+        let mut world = World::new(4, 2, Rule::from(gen_conway_dec()));
+        world.step();
+        world.load(&pattern, OverflowPolicy::Resize).unwrap();
+        assert_eq!(world.dimensions(), (5, 3));
+        assert_eq!(world.grid, bounded_grid(&[(0, 0), (2, 2), (4, 2)], 5, 3));
+        assert_eq!(world.generation(), 0);
+        world.step();
+        assert_eq!(world.swap_grid.bounds, Some((5, 3)));
+
+        // A sparse bounded pattern only grows the world as far as its live
+        // cells, not out to its bounds.
+        let sparse = bounded_grid(&[(1, 0), (5, 2)], 100_000, 100_000);
+        let mut world = World::new(4, 4, Rule::from(gen_conway_dec()));
+        world.load(&sparse, OverflowPolicy::Resize).unwrap();
+        assert_eq!(world.dimensions(), (6, 4));
+        assert_eq!(world.grid, bounded_grid(&[(1, 0), (5, 2)], 6, 4));
+
+        let mut world = World::new(4, 4, Rule::from(gen_conway_dec()));
+        let far = Grid::from_runs(&[&[(usize::MAX - 1, 1)]]);
+        assert!(matches!(
+            world.load(&far, OverflowPolicy::Resize),
+            Err(Error::TooLarge { .. })
+        ));
+        assert_eq!(world.dimensions(), (4, 4));
+    }
+
+    #[test]
+    fn test_state_at() {
+        let rule = Rule::from(gen_conway_dec());
+        let mut world = World::new(6, 6, rule.clone());
+        for &cell in [(1, 1), (2, 1), (3, 1), (3, 2), (2, 3)].iter() {
+            world.grid.insert(&cell);
+        }
+        let next = world.next_grid();
+        for x in 0..6 {
+            for y in 0..6 {
+                let next_state = world.next_state(&(x, y));
+                assert_eq!(rule.output_for(state_at(&world.grid, &(x, y))), next_state);
+                assert_eq!(next.contains(&(x, y)), next_state);
+            }
+        }
+        assert_eq!(state_at(&world.grid, &(2, 2)), 0b010_100_111);
+    }
+
+    #[test]
+    fn test_minimal() {
+        let glider = Grid::from(vec!["", "", "     # ", "      #", "    ###"]);
+        let world = World::minimal(&glider, Rule::from(gen_conway_dec()), 2).unwrap();
+        assert_eq!(world.dimensions(), (7, 7));
+        assert_eq!(
+            world.grid,
+            bounded_grid(&[(3, 2), (4, 3), (2, 4), (3, 4), (4, 4)], 7, 7)
+        );
+        let empty = World::minimal(&Grid::new(None), Rule::from(gen_conway_dec()), 1).unwrap();
+        assert_eq!(empty.dimensions(), (2, 2));
+
+        let conway = Rule::from(gen_conway_dec());
+        let spread = Grid::from_runs(&[&[(0, 1), (20_000, 1)]]);
+        assert!(matches!(
+            World::minimal(&spread, conway.clone(), 20_000),
+            Err(Error::TooLarge { .. })
+        ));
+        assert!(matches!(
+            World::minimal(&glider, conway.clone(), usize::MAX),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_visited() {
+        let glider = Grid::from(vec![" # ", "  #", "###"]);
+        let mut world = World::new(12, 12, Rule::from(gen_conway_dec()));
+        for cell in glider.grid.iter() {
+            world.grid.insert(cell);
+        }
+        world.step();
+        assert!(world.visited().is_empty());
+
+        world.set_trace(true);
+        let mut path = world.grid.grid.clone();
+        assert_eq!(world.visited(), &path);
+        let mut last = 0;
+        for _ in 0..16 {
+            world.step();
+            path.extend(world.grid.grid.iter());
+            assert!(world.visited().len() > last);
+            last = world.visited().len();
+        }
+        assert_eq!(world.visited(), &path);
+        assert!(world.visited().contains(&(2, 1)));
+        assert!(world.visited().contains(&(5, 6)));
+
+        world.load(&glider, OverflowPolicy::Error).unwrap();
+        assert_eq!(world.visited(), &glider.grid);
+        world.reset();
+        assert!(world.visited().is_empty());
+        world.load(&glider, OverflowPolicy::Error).unwrap();
+        world.set_trace(false);
+        assert!(world.visited().is_empty());
+        world.step();
+        assert!(world.visited().is_empty());
+    }
+
+    #[test]
+    fn test_from_runs() {
+        let blinker = Grid::from_runs(&[&[], &[(1, 3)]]);
+        assert_eq!(blinker, Grid::from(vec!["", " ###"]));
+        assert_eq!(blinker.population(), 3);
+        assert!(Grid::from_runs(&[&[(4, 0)]]).grid.is_empty());
+        assert!(Grid::from_runs(&[&[(usize::MAX, 2)]]).grid.is_empty());
+        assert_eq!(
+            Grid::from_runs(&[&[(usize::MAX - 1, 3)]]).iter_sorted().collect::<Vec<_>>(),
+            vec![(usize::MAX - 1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_world_size_limit() {
+        let rule = Rule::from(gen_conway_dec());
+        assert!(matches!(
+            World::try_new(usize::MAX, usize::MAX, rule.clone()),
+            Err(Error::TooLarge { .. })
+        ));
+        assert!(matches!(
+            World::try_new(MAX_CELLS, 2, rule.clone()),
+            Err(Error::TooLarge { limit: MAX_CELLS, .. })
+        ));
+        assert!(matches!(
+            World::try_new_with_limit(10, 11, rule.clone(), 100),
+            Err(Error::TooLarge {
+                width: 10,
+                height: 11,
+                limit: 100
+            })
+        ));
+        let world = World::try_new_with_limit(10, 10, rule, 100).unwrap();
+        assert_eq!(world.dimensions(), (10, 10));
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let table = Rule::from(gen_conway_dec()).truth_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 512);
+        assert_eq!(lines[16], "16: ... .#. ... -> 0");
+        // Three live cells in the top row bring the center to life.
+        assert_eq!(lines[7], "7: ### ... ... -> 1");
+
+        let table = Rule::with_mask(BigUint::from(0u32), VON_NEUMANN).truth_table();
+        assert_eq!(table.lines().count(), 32);
+        assert_eq!(table.lines().nth(4), Some("4:  .  .#.  .  -> 0"));
+    }
+
+    #[test]
+    fn test_apgcode() {
+        let conway = Rule::from(gen_conway_dec());
+        let block = Grid::from(vec!["", " ##", " ##"]);
+        assert_eq!(block.to_apgcode(&conway).as_deref(), Some("xs4_33"));
+        let beehive = Grid::from(vec![" ## ", "#  #", " ## "]);
+        assert_eq!(beehive.to_apgcode(&conway).as_deref(), Some("xs6_696"));
+        let blinker = Grid::from(vec!["###"]);
+        assert_eq!(blinker.to_apgcode(&conway).as_deref(), Some("xp2_7"));
+        let glider = Grid::from(vec![" # ", "  #", "###"]);
+        assert_eq!(glider.to_apgcode(&conway).as_deref(), Some("xq4_153"));
+        assert_eq!(Grid::new(None).to_apgcode(&conway), None);
+
+        let spread = Grid::from(vec!["#", "", "", "", "", "", "#      #"]);
+        assert_eq!(spread.wechsler(), "1z2y22");
+    }
+
+    #[test]
+    fn test_orbit_size() {
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
+        }
+        assert_eq!(blinker.orbit_size(10), Some(2));
+        assert_eq!(blinker.orbit_size(1), None);
+
+        // Dies at once, then stays empty.
+        let mut domino = World::new(4, 4, Rule::from(gen_conway_dec()));
+        domino.grid.insert(&(1, 1));
+        domino.grid.insert(&(2, 1));
+        assert_eq!(domino.orbit_size(10), Some(2));
+    }
+
+    #[test]
+    fn test_padded() {
+        let dot = Grid::from(vec!["", "  #"]);
+        assert_eq!(dot.padded(3, 3, Align::Center), bounded_grid(&[(1, 1)], 3, 3));
+        assert_eq!(dot.padded(3, 3, Align::TopLeft), bounded_grid(&[(0, 0)], 3, 3));
+        assert_eq!(dot.padded(3, 3, Align::Right), bounded_grid(&[(2, 1)], 3, 3));
+
+        let domino = Grid::from(vec!["##"]);
+        assert_eq!(
+            domino.padded(5, 2, Align::Bottom),
+            bounded_grid(&[(1, 1), (2, 1)], 5, 2)
+        );
+        assert_eq!(domino.padded(1, 1, Align::Center), bounded_grid(&[(0, 0)], 1, 1));
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let cells = [(3, 1), (0, 2), (2, 0), (1, 1), (0, 0)];
+        let mut forward = Grid::new(None);
+        let mut backward = Grid::new(None);
+        for cell in cells.iter() {
+            forward.insert(cell);
+        }
+        for cell in cells.iter().rev() {
+            backward.insert(cell);
+        }
+        let sorted: Vec<Coords> = forward.iter_sorted().collect();
+        assert_eq!(sorted, vec![(0, 0), (2, 0), (1, 1), (3, 1), (0, 2)]);
+        assert!(forward.iter_sorted().eq(backward.iter_sorted()));
+    }
+
+    #[test]
+    #[cfg(feature = "terminal")]
+    fn test_ticks() {
+        assert_eq!(ticks(12, 5), vec![0, 5, 10]);
+        assert_eq!(ticks(10, 5), vec![0, 5]);
+        assert_eq!(ticks(25, 10), vec![0, 10, 20]);
+        assert_eq!(ticks(3, 10), vec![0]);
+        assert!(ticks(0, 10).is_empty());
+        assert!(ticks(8, 0).is_empty());
+
+        let mut world = World::new(6, 6, Rule::from(gen_conway_dec()));
+        world.grid.insert(&(0, 0));
+        let mut canvas = Widget::new(7, 7);
+        world.render_with_ruler(&mut canvas, 5);
+        let ch = |x, y| canvas.get(x, y).unwrap().ch();
+        assert_eq!((ch(1, 0), ch(2, 0), ch(6, 0)), ('\u{b7}', ' ', '\u{b7}'));
+        assert_eq!((ch(0, 1), ch(0, 2), ch(0, 6)), ('\u{b7}', ' ', '\u{b7}'));
+        assert_eq!((ch(1, 1), ch(2, 2)), ('\u{2588}', ' '));
+    }
+
+    #[test]
+    #[cfg(feature = "terminal")]
+    fn test_shade() {
+        assert_eq!(shade(0.0), ' ');
+        assert_eq!(shade(0.1), '\u{2591}');
+        assert_eq!(shade(0.5), '\u{2592}');
+        assert_eq!(shade(0.9), '\u{2593}');
+        assert_eq!(shade(1.0), '\u{2588}');
+
+        let mut world = World::new(4, 4, Rule::from(gen_conway_dec()));
+        for &cell in [(0, 0), (1, 0), (0, 1), (1, 1), (2, 2)].iter() {
+            world.grid.insert(&cell);
+        }
+        let mut canvas = Widget::new(2, 2);
+        world.render_downscaled(&mut canvas);
+        let ch = |x, y| canvas.get(x, y).unwrap().ch();
+        assert_eq!(ch(0, 0), '\u{2588}');
+        assert_eq!(ch(1, 0), ' ');
+        assert_eq!(ch(1, 1), '\u{2591}');
+    }
+
+    #[test]
+    fn test_checked_offset() {
+        let origin = Cell { x: 0, y: 0 };
+        assert_eq!(origin.checked_offset(-1, 0), None);
+        assert_eq!(origin.checked_offset(0, -1), None);
+        assert_eq!(origin.checked_offset(1, 1), Some(Cell { x: 1, y: 1 }));
+        assert_eq!(origin.neighbors().count(), 3);
+
+        let inner = Cell::from((3, 5));
+        assert_eq!(inner.checked_offset(-1, 1), Some(Cell { x: 2, y: 6 }));
+        assert_eq!(inner.checked_offset(-3, -5), Some(Cell { x: 0, y: 0 }));
+        assert_eq!(inner.checked_offset(-4, 0), None);
+        assert_eq!(Cell { x: usize::MAX, y: 0 }.checked_offset(1, 0), None);
+        let around: Vec<Coords> = inner.neighbors().map(Coords::from).collect();
+        assert_eq!(
+            around,
+            vec![(2, 4), (3, 4), (4, 4), (2, 5), (4, 5), (2, 6), (3, 6), (4, 6)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_compose_step() {
+        let conway = Rule::from(gen_conway_dec());
+        let von_neumann = Rule::with_mask(BigUint::from(0x1234_5678u32), VON_NEUMANN);
+        for (edges, rule) in [
+            (DEAD_EDGES, conway.clone()),
+            ((EdgeMode::Wrap, EdgeMode::Reflect), conway),
+            ((EdgeMode::Wrap, EdgeMode::Wrap), von_neumann),
+        ] {
+            // Odd sizes leave half-empty blocks along the right and bottom.
+            let mut world = World::new(15, 11, rule);
+            world.set_edges(edges.0, edges.1);
+            world.gen_mode(SeedMode::Centered, 155);
+            let mut by_steps = world.clone();
+            for k in 1..5 {
+                world.compose_step(k);
+                by_steps.step_n(k);
+                assert_eq!(world.grid, by_steps.grid);
+            }
+            assert_eq!(world.generation(), 10);
+            assert_eq!(by_steps.generation(), 10);
+        }
+    }
+
+    #[test]
+    fn test_viewport_to_cell() {
+        let world = World::new(8, 6, Rule::from(gen_conway_dec()));
+        let origin = Cell { x: 0, y: 0 };
+        assert_eq!(world.viewport_to_cell((0, 0), origin), Some(Cell { x: 0, y: 0 }));
+        assert_eq!(world.viewport_to_cell((7, 5), origin), Some(Cell { x: 7, y: 5 }));
+        assert_eq!(world.viewport_to_cell((8, 5), origin), None);
+        assert_eq!(world.viewport_to_cell((7, 6), origin), None);
+
+        let scrolled = Cell { x: 5, y: 2 };
+        assert_eq!(world.viewport_to_cell((2, 3), scrolled), Some(Cell { x: 7, y: 5 }));
+        assert_eq!(world.viewport_to_cell((3, 0), scrolled), None);
+        let far = Cell { x: usize::MAX, y: 0 };
+        assert_eq!(world.viewport_to_cell((1, 0), far), None);
+    }
+
+    #[test]
+    fn test_overlap_count() {
+        let field = Grid::from(vec!["#   ", " #  ", "    ", "   #"]);
+        let block = Grid::from(vec!["##", "##"]);
+        assert_eq!(field.overlap_count(&block, Cell { x: 0, y: 0 }), 2);
+        assert_eq!(field.overlap_count(&block, Cell { x: 1, y: 0 }), 1);
+        assert_eq!(field.overlap_count(&block, Cell { x: 2, y: 1 }), 0);
+        assert_eq!(field.overlap_count(&block, Cell { x: usize::MAX, y: 0 }), 0);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let block = Grid::from(vec!["", " ##", " ##"]).symmetries();
+        assert_eq!(block.len(), 7);
+        assert!(block.iter().eq(Symmetry::ALL.iter().cloned()));
+
+        let glider = Grid::from(vec![" # ", "  #", "###"]).symmetries();
+        assert!(glider.is_empty());
+
+        // An L tromino only mirrors along one diagonal.
+        let diagonal = Grid::from(vec!["#", "##"]).symmetries();
+        assert_eq!(diagonal.iter().collect::<Vec<_>>(), vec![Symmetry::FlipAntiDiagonal]);
+
+        let domino = Grid::from(vec!["##"]).symmetries();
+        assert_eq!(
+            domino.iter().collect::<Vec<_>>(),
+            vec![
+                Symmetry::FlipHorizontal,
+                Symmetry::FlipVertical,
+                Symmetry::Rotate180
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_activation() {
+        let glider = Grid::from(vec![" # ", "  #", "###"]);
+        let mut world = World::new(12, 12, Rule::from(gen_conway_dec()));
+        world.load(&glider, OverflowPolicy::Error).unwrap();
+        // Every 4 generations the glider moves a cell down and right, so its
+        // bottom right corner reaches (6, 6) at generation 16.
+        assert_eq!(world.first_activation(Cell { x: 6, y: 6 }, 40), Some(16));
+        assert_eq!(world.generation(), 16);
+        assert_eq!(world.first_activation(Cell { x: 6, y: 6 }, 40), Some(16));
+        assert_eq!(world.first_activation(Cell { x: 0, y: 11 }, 20), None);
+        assert_eq!(world.generation(), 20);
+    }
+
+    #[test]
+    fn test_with_bounds() {
+        let unbounded = Grid::from(vec!["#  ", "   ", "  #"]);
+        let bounded = unbounded.with_bounds(Some((2, 2)));
+        assert_eq!(bounded, bounded_grid(&[(0, 0)], 2, 2));
+
+        let wide = bounded_grid(&[(0, 0), (4, 1)], 5, 2);
+        let loose = wide.with_bounds(None);
+        assert_eq!(loose.bounds, None);
+        assert_eq!(loose, Grid::from(vec!["#", "    #"]));
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_assert_step_equivalence() {
+        let mut world = World::new(16, 16, Rule::from(gen_conway_dec()));
+        world.gen_mode(SeedMode::Centered, 161);
+        let mut clone = world.clone();
+        assert_step_equivalence(&mut world, &mut clone, 20);
+        assert_eq!(world.generation(), 20);
+
+        let mut other_rule = world.clone();
+        other_rule.set_rule(Rule::named("HighLife").unwrap());
+        world.reset();
+        other_rule.reset();
+        for x in 0..16 {
+            world.grid.insert(&(x, 8));
+            other_rule.grid.insert(&(x, 8));
+        }
+        let diverged = std::panic::catch_unwind(move || {
+            assert_step_equivalence(&mut world, &mut other_rule, 20)
+        });
+        assert!(diverged.is_err());
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        let grid = Grid::from(vec!["# #", " # "]);
+        assert_eq!(grid.hamming_distance(&grid), 0);
+        let mut other = grid.clone();
+        other.insert(&(0, 1));
+        assert_eq!(grid.hamming_distance(&other), 1);
+        assert_eq!(other.hamming_distance(&grid), 1);
+        assert_eq!(grid.hamming_distance(&Grid::from(vec![" # "])), 4);
+    }
+
+    #[test]
+    fn test_sensitivity() {
+        let conway = Rule::from(gen_conway_dec());
+        // A lone cell next to nothing dies at once and leaves the block be.
+        let block = Grid::from_str_bounded(&["", " ##", " ##"], 40, 40);
+        let stable = sensitivity(&conway, &block, Cell { x: 30, y: 30 }, 60).unwrap();
+        assert_eq!(stable.len(), 60);
+        assert!(stable.iter().all(|&d| d == 0));
+
+        // The R-pentomino takes over a thousand generations to settle, and
+        // removing one of its cells sends it somewhere else entirely.
+        let r_pentomino = Grid::from(vec![" ##", "## ", " # "]).padded(40, 40, Align::Center);
+        let chaotic = sensitivity(&conway, &r_pentomino, Cell { x: 19, y: 20 }, 60).unwrap();
+        assert!(chaotic[59] > 20);
+
+        assert!(matches!(
+            sensitivity(&conway, &Grid::new(None), Cell { x: 0, y: 0 }, 1),
+            Err(Error::Unbounded)
+        ));
+        assert!(matches!(
+            sensitivity(&conway, &Grid::new(Some((20_000, 20_000))), Cell { x: 0, y: 0 }, 1),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let world = World::new(10, 20, Rule::from(gen_conway_dec()));
+        assert_eq!(world.dimensions(), (10, 20));
+        assert_eq!(world.width(), 10);
+        assert_eq!(world.height(), 20);
+    }
+
+    #[test]
+    fn test_step_region() {
+        let mut world = World::new(12, 5, Rule::from(gen_conway_dec()));
+        // A blinker on each side; only the right one is in the region.
+        for x in 1..4 {
+            world.grid.insert(&(x, 2));
+            world.grid.insert(&(x + 7, 2));
+        }
+        world.step_region(Cell { x: 6, y: 0 }, Cell { x: 11, y: 4 });
+        assert_eq!(
+            world.grid,
+            bounded_grid(&[(1, 2), (2, 2), (3, 2), (9, 1), (9, 2), (9, 3)], 12, 5)
+        );
+        assert_eq!(world.generation(), 1);
+
+        // The frozen left blinker feeds births on the region's border.
+        let mut world = World::new(6, 5, Rule::from(gen_conway_dec()));
+        for y in 1..4 {
+            world.grid.insert(&(1, y));
+        }
+        world.step_region(Cell { x: 2, y: 0 }, Cell { x: 5, y: 4 });
+        assert_eq!(
+            world.grid,
+            bounded_grid(&[(1, 1), (1, 2), (1, 3), (2, 2)], 6, 5)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_ash_census() {
+        let mut world = World::new(40, 40, Rule::from(gen_conway_dec()));
+        world.gen_mode(SeedMode::Centered, 166);
+        let census = world.ash_census(400);
+        assert!(census["xs4_33"] >= 1);
+        assert_eq!(census.values().sum::<usize>(), world.grid.components().len());
+    }
+
+    #[test]
+    fn test_scaled() {
+        let mut grid = Grid::bounded(2, 2);
+        grid.insert(&(1, 0));
+        let scaled = grid.scaled(3).unwrap();
+        assert_eq!(scaled.bounds, Some((6, 6)));
+        assert_eq!(scaled.population(), 9);
+        for y in 0..3 {
+            for x in 3..6 {
+                assert!(scaled.is_alive((x, y)));
+            }
+        }
+        assert_eq!(grid.scaled(1).unwrap(), grid);
+
+        assert!(matches!(
+            Grid::bounded(usize::MAX / 2, 1).scaled(3),
+            Err(Error::TooLarge { .. })
+        ));
+        let mut far = Grid::unbounded();
+        far.insert(&(usize::MAX / 2, 0));
+        assert!(far.scaled(2).is_err());
+        assert_eq!(far.scaled(1).unwrap(), far);
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let input = io::Cursor::new("#  #\n\n ##\n");
+        let grid = Grid::from_reader(input).unwrap();
+        assert_eq!(grid.bounds, None);
+        let cells: Vec<Coords> = grid.iter_sorted().collect();
+        assert_eq!(cells, vec![(0, 0), (3, 0), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_popcount() {
+        assert_eq!(Rule::from(BigUint::from(0u32)).popcount(), 0);
+        assert_eq!(Rule::from(gen_conway_dec()).popcount(), 140);
+    }
+
+    #[test]
+    #[cfg(feature = "terminal")]
+    fn test_render_clips_to_canvas() {
+        let mut world = World::new(20, 3, Rule::from(gen_conway_dec()));
+        for x in 0..20 {
+            world.grid.insert(&(x, 1));
+        }
+        let mut canvas = Widget::new(5, 3);
+        world.render(&mut canvas);
+        for x in 0..5 {
+            assert_eq!(canvas.get(x, 0).unwrap().ch(), ' ');
+            assert_eq!(canvas.get(x, 1).unwrap().ch(), '\u{2588}');
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "terminal")]
+    fn test_render_tiled() {
+        let mut world = World::new(2, 2, Rule::from(gen_conway_dec()));
+        world.grid.insert(&(0, 0));
+        // Far more tiles than fit, which only the canvas's size bounds.
+        let mut canvas = Widget::new(5, 3);
+        world.render_tiled(&mut canvas, usize::MAX, usize::MAX);
+        for x in 0..5 {
+            for y in 0..3 {
+                let expected = if x % 2 == 0 && y % 2 == 0 { '\u{2588}' } else { ' ' };
+                assert_eq!(canvas.get(x, y).unwrap().ch(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tiled() {
+        let unit = Grid::from_str_bounded(&["#"], 2, 2);
+        let world = World::tiled(&unit, 3, 3, Rule::from(gen_conway_dec())).unwrap();
+        assert_eq!(world.dimensions(), (6, 6));
+        let cells: Vec<Coords> = world.grid.iter_sorted().collect();
+        let mut expected = Vec::new();
+        for y in 0..3 {
+            for x in 0..3 {
+                expected.push((2 * x, 2 * y));
+            }
+        }
+        assert_eq!(cells, expected);
+
+        let rule = Rule::from(gen_conway_dec());
+        for &(tiles_x, tiles_y) in &[(usize::MAX, 1), (1, usize::MAX), (100_000, 1_000)] {
+            assert!(matches!(
+                World::tiled(&unit, tiles_x, tiles_y, rule.clone()),
+                Err(Error::TooLarge { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_custom_transition() {
+        /// Conway's Game of Life with live and dead swapped
+        struct InvertedConway;
+
+        impl Transition for InvertedConway {
+            fn next(&self, center: bool, state: usize) -> bool {
+                let live = (state & !(1 << 4)).count_ones();
+                !(live == 3 || center && live == 2)
+            }
+        }
+
+        let mut custom = World::new(6, 6, InvertedConway);
+        let mut conway = World::new(6, 6, Rule::from(gen_conway_dec()));
+        for &cell in [(1, 2), (2, 2), (3, 2), (3, 1)].iter() {
+            custom.grid.insert(&cell);
+            conway.grid.insert(&cell);
+        }
+        custom.step();
+        conway.step();
+        for x in 0..6 {
+            for y in 0..6 {
+                assert_ne!(custom.grid.contains(&(x, y)), conway.grid.contains(&(x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_line() {
+        let mut world = World::new(5, 5, Rule::named("Conway").unwrap());
+        world.grid = Grid::from_str_bounded(&["", " ###"], 5, 5);
+        world.step();
+        assert_eq!(world.status_line(), "gen 1 | pop 3 | \u{394} 4 | rule B3/S23");
+        world.set_rule(Rule::from(BigUint::from(1802u32)));
+        assert_eq!(world.status_line(), "gen 1 | pop 3 | \u{394} 4 | rule 1802");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_session_round_trip() {
+        let path = std::env::temp_dir().join("hemoglobin-test-session.json");
+        let mut world = World::new(7, 5, Rule::with_mask(BigUint::from(1802u32), VON_NEUMANN));
+        world.grid = Grid::from_str_bounded(&["", " ##", "   #"], 7, 5);
+        world.set_edges(EdgeMode::Wrap, EdgeMode::Reflect);
+        world.step();
+        let session = Session {
+            world,
+            viewport_origin: Cell { x: 2, y: 1 },
+            style: Style {
+                live: 'o',
+                dead: '.',
+            },
+        };
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        assert_eq!(loaded.world.grid, session.world.grid);
+        assert_eq!(loaded.world.rule(), session.world.rule());
+        assert_eq!(loaded.world.generation(), 1);
+        assert_eq!(loaded.world.last_activity(), session.world.last_activity());
+        assert_eq!(loaded.world.edges(), (EdgeMode::Wrap, EdgeMode::Reflect));
+        assert_eq!(loaded.viewport_origin, session.viewport_origin);
+        assert_eq!(loaded.style, session.style);
+
+        // Rules over tiny neighborhoods load back too.
+        let mut pair = [false; 9];
+        pair[3] = true;
+        pair[4] = true;
+        let session = Session {
+            world: World::new(3, 3, Rule::with_mask(BigUint::from(6u32), pair)),
+            viewport_origin: Cell { x: 0, y: 0 },
+            style: Style::default(),
+        };
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        assert_eq!(loaded.world.rule(), session.world.rule());
+    }
+
+    #[test]
+    fn test_is_garden_of_eden() {
+        let conway = Rule::from(gen_conway_dec());
+        // The center would need at most three live neighbors, leaving too few
+        // for the edges and corners around it.
+        let full = Grid::from_str_bounded(&["###", "###", "###"], 3, 3);
+        assert!(full.is_garden_of_eden(&conway).unwrap());
+        // The other phase of the blinker.
+        let blinker = Grid::from_str_bounded(&["", "###"], 3, 3);
+        assert!(!blinker.is_garden_of_eden(&conway).unwrap());
+        assert!(matches!(
+            Grid::bounded(5, 4).is_garden_of_eden(&conway),
+            Err(Error::TooLarge { limit: 16, .. })
+        ));
+        assert!(matches!(
+            Grid::bounded(usize::MAX, 2).is_garden_of_eden(&conway),
+            Err(Error::TooLarge { limit: 16, .. })
+        ));
+        assert!(matches!(Grid::unbounded().is_garden_of_eden(&conway), Err(Error::Unbounded)));
+    }
+
+    #[test]
+    fn test_iter_with_counts() {
+        let blinker = Grid::from(vec!["", " ###"]);
+        let counts: Vec<(Cell, u8)> = blinker.iter_with_counts().collect();
+        assert_eq!(
+            counts,
+            vec![
+                (Cell { x: 1, y: 1 }, 1),
+                (Cell { x: 2, y: 1 }, 2),
+                (Cell { x: 3, y: 1 }, 1),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_run_to_json() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        world.grid = Grid::from_str_bounded(&["", "", " ###"], 5, 5);
+        let json: serde_json::Value = serde_json::from_str(&world.run_to_json(3)).unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0]["population"], 3);
+        assert_eq!(entries[0]["bbox"]["width"], 3);
+        assert_eq!(entries[1]["generation"], 1);
+        assert_eq!(entries[1]["bbox"]["height"], 3);
+    }
+
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_gen_with_border() {
+        let mut world = World::new(40, 30, Rule::from(gen_conway_dec()));
+        world.gen_with_border(3);
+        assert!(world.population() > 0);
+        for &(x, y) in world.grid.grid.iter() {
+            assert!((3..37).contains(&x) && (3..27).contains(&y));
+        }
+        // A border reaching the middle leaves nothing to fill.
+        world.gen_with_border(15);
+        assert_eq!(world.population(), 0);
+    }
+
+    #[test]
+    fn test_set_birth_and_survival() {
+        let mut rule = Rule::from(gen_conway_dec());
+        rule.set_birth(6, true);
+        assert_eq!(rule, Rule::from_bs("B36/S23").unwrap());
+        assert_eq!(rule.to_string(), Rule::from_bs("B36/S23").unwrap().to_string());
+        rule.set_birth(6, false);
+        rule.set_survival(2, false);
+        assert_eq!(rule, Rule::from_bs("B3/S3").unwrap());
     }
 
-    fn x_bound(&self) -> Option<usize> {
-        match self.bounds {
-            None => None,
-            Some((w, _)) => Some(w),
+    #[test]
+    fn test_signed_grid_glider() {
+        // A glider heading up and to the left from the origin.
+        let mut grid = SignedGrid::new();
+        for &cell in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 2)].iter() {
+            grid.insert(cell);
         }
+        let conway = Rule::from(gen_conway_dec());
+        for _ in 0..8 {
+            grid.step(&conway);
+        }
+        let mut expected = SignedGrid::new();
+        for &cell in [(-2, -2), (-1, -2), (0, -2), (-2, -1), (-1, 0)].iter() {
+            expected.insert(cell);
+        }
+        assert_eq!(grid, expected);
     }
 
-    fn y_bound(&self) -> Option<usize> {
-        match self.bounds {
-            None => None,
-            Some((_, h)) => Some(h),
-        }
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_interestingness() {
+        let conway = interestingness(&Rule::from(gen_conway_dec()), 182, 32, 100).unwrap();
+        let all_dead = interestingness(&Rule::from(BigUint::from(0u32)), 182, 32, 100).unwrap();
+        let all_alive = (BigUint::from(1u32) << 512) - BigUint::from(1u32);
+        let all_alive = interestingness(&Rule::from(all_alive), 182, 32, 100).unwrap();
+        assert!(conway > all_dead + 0.25);
+        assert!(conway > all_alive + 0.25);
+        assert!(matches!(
+            interestingness(&Rule::from(gen_conway_dec()), 182, 20_000, 1),
+            Err(Error::TooLarge { .. })
+        ));
     }
 
-    pub fn gen(&mut self) {
-        match self.bounds {
-            None => {}
-            Some(_) => {
-                self.grid.clear();
-                for x in 0..self.x_bound().unwrap() {
-                    for y in 0..self.y_bound().unwrap() {
-                        if rand::thread_rng().gen_bool(1.0/10.0) {
-                            self.insert(&(x, y));
-                        }
-                    }
-                }
-            }
-        }
+    #[test]
+    fn test_spawn_simulation() {
+        let mut world = World::new(5, 5, Rule::from(gen_conway_dec()));
+        world.grid = Grid::from_str_bounded(&["", "", " ###"], 5, 5);
+        let grids: Vec<Grid> = world.spawn_simulation(5).iter().collect();
+        assert_eq!(grids.len(), 5);
+        assert_eq!(grids[4], Grid::from_str_bounded(&["", "  #", "  #", "  #"], 5, 5));
     }
-}
 
-impl<'a> From<Vec<&'a str>> for Grid {
-    /// Returns a Grid interpreted from a string representation
-    ///
-    /// # Arguments
-    ///
-    /// * `s` - Representation of the grid. Each element of the vector
-    /// represents a row in the grid. Hash marks # indicate live cells.
-    /// For example ovec!["#  ", "   ", " # "] represents a grid with live
-    /// cells at (0, 0) and (2, 1).
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// let grid = hemoglobin::Grid::from(vec!["#  ", "   ", " # "]);
-    /// ```
-    fn from(s: Vec<&str>) -> Self {
-        let mut result = Grid::new(None);
-        for (y, row) in s.iter().enumerate() {
-            for (x, c) in row.chars().enumerate() {
-                if c == '#' {
-                    result.insert(&(x, y));
-                }
+    #[test]
+    fn test_periodic_tile() {
+        let mut lattice = Grid::unbounded();
+        for x in 0..4 {
+            for y in 0..3 {
+                lattice.insert(&(5 + 3 * x, 1 + 2 * y));
             }
         }
-        result
+        assert_eq!(lattice.periodic_tile(), Some((3, 2)));
+        lattice.insert(&(6, 1));
+        assert_eq!(lattice.periodic_tile(), None);
+        assert_eq!(Grid::from(vec!["#"]).periodic_tile(), None);
     }
-}
 
-pub struct Rule {
-    dec: BigUint,
-    bin: BitVec,
-}
+    #[test]
+    fn test_core_simulation() {
+        // Also run by `cargo test --no-default-features`, so it sticks to
+        // what builds without the terminal and random features.
+        let mut world = World::new(5, 5, Rule::named("Conway").unwrap());
+        let blinker = Grid::from_str_bounded(&["", "", " ###"], 5, 5);
+        world.load(&blinker, OverflowPolicy::Error).unwrap();
+        world.step();
+        assert_ne!(world.grid, blinker);
+        world.step();
+        assert_eq!(world.grid, blinker);
+    }
 
-impl From<BigUint> for Rule {
-    fn from(x: BigUint) -> Self {
-        Rule {
-            dec: x.clone(),
-            bin: {
-                let result_reversed = BitVec::from_bytes(&x.to_bytes_be());
-                let mut result = BitVec::from_elem(512, false);
-                for i in 0..result_reversed.len() {
-                    result.set(i, result_reversed[result_reversed.len() - i - 1]);
-                }
-                result
-            },
+    #[test]
+    fn test_render_rgba() {
+        let mut world = World::new(3, 2, Rule::from(gen_conway_dec()));
+        world.grid.insert(&(1, 0));
+        let (width, height, rgba) = world.render_rgba(2);
+        assert_eq!((width, height), (6, 4));
+        assert_eq!(rgba.len(), 6 * 4 * 4);
+        let pixel = |x: usize, y: usize| &rgba[(y * 6 + x) * 4..][..4];
+        for &(x, y) in [(2, 0), (3, 0), (2, 1), (3, 1)].iter() {
+            assert_eq!(pixel(x, y), [0, 0, 0, 0xff]);
         }
+        assert_eq!(pixel(1, 0), [0xff; 4]);
+        assert_eq!(pixel(2, 2), [0xff; 4]);
+
+        assert!(matches!(
+            world.try_render_rgba(u32::MAX),
+            Err(Error::TooLarge { limit: MAX_PIXELS, .. })
+        ));
+        let wide = World::new(MAX_PIXELS, 1, Rule::from(gen_conway_dec()));
+        assert!(matches!(
+            wide.try_render_rgba(2),
+            Err(Error::TooLarge { limit: MAX_PIXELS, .. })
+        ));
+        let endless = World::new(usize::MAX, 0, Rule::from(gen_conway_dec()));
+        assert!(matches!(endless.try_render_rgba(1), Err(Error::TooLarge { .. })));
     }
-}
 
-impl From<String> for Rule {
-    fn from(s: String) -> Self {
-        Rule::from(s.parse::<BigUint>().unwrap())
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_blend() {
+        let a = Grid::from_str_bounded(&["##", " #"], 4, 4);
+        let b = Grid::from_str_bounded(&["", " #", "  ##"], 4, 4);
+        let mut rng = StdRng::seed_from_u64(187);
+        assert_eq!(Grid::blend(&a, &b, 0.0, &mut rng), a);
+        assert_eq!(Grid::blend(&a, &b, 1.0, &mut rng), b);
+        let halfway = Grid::blend(&a, &b, 0.5, &mut rng);
+        assert!(halfway.is_alive((1, 1)));
+        assert!(halfway.grid.is_subset(&a.grid.union(&b.grid).cloned().collect()));
     }
-}
 
-pub struct World {
-    rule: Rule,
-    grid: Grid,
-    swap_grid: Grid,
-}
+    #[test]
+    fn test_record() {
+        let glider = Grid::from_str_bounded(&[" # ", "  #", "###"], 8, 8);
+        let mut world = World::new(8, 8, Rule::from(gen_conway_dec()));
+        world.load(&glider, OverflowPolicy::Error).unwrap();
+        let recording = world.record(10);
+        assert_eq!(recording.len(), 11);
+        assert_eq!(recording.frame(10), Some(&world.grid));
+        assert_eq!(recording.frame(11), None);
 
-impl World {
-    pub fn new(width: usize, height: usize, rule: Rule) -> Self {
-        World {
-            rule: rule,
-            grid: Grid::new(Some((width, height))),
-            swap_grid: Grid::new(Some((width, height))),
+        let mut replay = World::new(8, 8, Rule::from(gen_conway_dec()));
+        replay.load(&glider, OverflowPolicy::Error).unwrap();
+        replay.step_n(4);
+        assert_eq!(recording.frame(4), Some(&replay.grid));
+
+        // Later recordings are numbered from where the world was.
+        let later = world.record(2);
+        assert_eq!(later.frame(9), None);
+        assert_eq!(later.frame(10), recording.frame(10));
+    }
+
+    #[test]
+    fn test_canonical_rule() {
+        // Lone cells survive and are born next to a lone cell on their left,
+        // or, in the mirror image, on their right.
+        let left = Rule::from((BigUint::from(1u32) << 16) + (BigUint::from(1u32) << 8));
+        let right = Rule::from((BigUint::from(1u32) << 16) + (BigUint::from(1u32) << 32));
+        assert_eq!(left.turned(Symmetry::FlipHorizontal), Some(right.clone()));
+        assert_eq!(left.canonical_rule(), right.canonical_rule());
+        let above = Rule::from((BigUint::from(1u32) << 16) + (BigUint::from(1u32) << 2));
+        assert_eq!(left.canonical_rule(), above);
+
+        let conway = Rule::from(gen_conway_dec());
+        assert_eq!(conway.canonical_rule(), conway);
+
+        // Symmetries keep the von Neumann neighborhood in place.
+        let north = Rule::with_mask(BigUint::from(1u32) << 1, VON_NEUMANN);
+        let south = Rule::with_mask(BigUint::from(1u32) << 16, VON_NEUMANN);
+        assert_eq!(north.canonical_rule(), south.canonical_rule());
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut world: World = World::new(8, 8, Rule::named("Conway").unwrap());
+        world.fill_rect(Cell { x: 2, y: 2 }, Cell { x: 4, y: 4 }, true);
+        assert_eq!(world.population(), 9);
+        world.fill_rect(Cell { x: 0, y: 3 }, Cell { x: 7, y: 3 }, false);
+        let expected: HashSet<Coords> = [(2, 2), (3, 2), (4, 2), (2, 4), (3, 4), (4, 4)]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(world.grid.grid, expected);
+        world.fill_rect(Cell { x: 6, y: 6 }, Cell { x: 20, y: 20 }, true);
+        assert_eq!(world.population(), 10);
+    }
+
+    #[test]
+    fn test_heat_map() {
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
         }
+        let heat = blinker.heat_map(6);
+        for &(x, y) in &[(1, 2), (3, 2), (2, 1), (2, 3)] {
+            assert_eq!(heat[y][x], 6);
+        }
+        assert_eq!(heat[2][2], 0);
+        assert_eq!(heat.iter().flatten().sum::<u32>(), 24);
     }
 
-    fn decide_next_state(&self, cell: &Cell) -> bool {
-        let state = get_state(&self.grid, cell);
-        self.rule.bin[state]
+    #[test]
+    fn test_from_text() {
+        let grid = Grid::from_text("HI");
+        // H fills its five columns and I, one column on, its middle three.
+        assert_eq!(grid.extent(), (GLYPH_WIDTH + 1 + 4, GLYPH_HEIGHT));
+        assert!(grid.is_alive((0, 0)));
+        assert!(grid.is_alive((2, 3)));
+        assert!(!grid.is_alive((2, 0)));
+        assert!(grid.is_alive((8, 0)));
+        assert!(grid.is_alive((8, 6)));
+        assert!(!grid.is_alive((6, 3)));
+        assert_eq!(grid.population(), 17 + 11);
+
+        let lines = Grid::from_text("h\n?");
+        assert_eq!(lines.grid, {
+            let mut expected = Grid::from_text("H");
+            for &(x, y) in Grid::from_text("?").grid.iter() {
+                expected.insert(&(x, y + GLYPH_HEIGHT + 1));
+            }
+            expected.grid
+        });
+        assert_eq!(Grid::from_text("~ ").population(), 0);
     }
 
-    pub fn step(&mut self) {
-        self.swap_grid.grid.clear();
+    #[test]
+    #[cfg(feature = "random")]
+    fn test_is_explosive() {
+        let seeds = Rule::named("Seeds").unwrap();
+        let conway = Rule::from(gen_conway_dec());
+        assert!(is_explosive(&seeds, 141, 80, 1000, 0.2).unwrap());
+        assert!(!is_explosive(&conway, 141, 80, 200, 0.2).unwrap());
+        assert!(!is_explosive(&seeds, 141, 80, 0, 0.2).unwrap());
+        assert!(matches!(
+            is_explosive(&seeds, 141, usize::MAX, 1, 0.2),
+            Err(Error::TooLarge { .. })
+        ));
+    }
 
-        for x in 0..self.grid.x_bound().unwrap() {
-            for y in 0..self.grid.y_bound().unwrap() {
-                let cell = (x, y);
-                if self.decide_next_state(&cell) {
-                    self.swap_grid.insert(&cell);
+    #[test]
+    fn test_contains_pattern() {
+        let block = Grid::from(vec!["##", "##"]);
+        let mut grid = Grid::bounded(20, 10);
+        for &(x, y) in &[(7, 3), (8, 3), (7, 4), (8, 4), (15, 8)] {
+            grid.insert(&(x, y));
+        }
+        assert_eq!(grid.contains_pattern(&block), Some(Cell { x: 7, y: 3 }));
+        let shifted = Grid::from(vec!["", "   ##", "   ##"]);
+        assert_eq!(grid.contains_pattern(&shifted), Some(Cell { x: 7, y: 3 }));
+        // A lone cell matches every live cell, the topmost first.
+        let cell = Grid::from(vec!["#"]);
+        assert_eq!(grid.contains_pattern(&cell), Some(Cell { x: 7, y: 3 }));
+        assert_eq!(grid.contains_pattern(&Grid::from(vec!["###"])), None);
+    }
+
+    #[test]
+    fn test_outer_totalistic_table() {
+        let conway = Rule::from(gen_conway_dec());
+        let table = conway.outer_totalistic_table().unwrap();
+        assert_eq!(table, conway.response_table());
+        assert!(table[0][3] && table[1][2] && table[1][3] && !table[1][4]);
+        let ot = OuterTotalisticRule::from_bs("B3/S23").unwrap();
+        assert_eq!(ot.outer_totalistic_table(), Some(table));
+        // Birth from the left neighbor alone, but not from the right one.
+        let lopsided = Rule::from(BigUint::from(1u32) << 8);
+        assert_eq!(lopsided.outer_totalistic_table(), None);
+        let von_neumann = Rule::with_mask(BigUint::from(1u32), VON_NEUMANN);
+        assert_eq!(von_neumann.outer_totalistic_table(), None);
+
+        for &(x_edge, y_edge) in &[DEAD_EDGES, (EdgeMode::Wrap, EdgeMode::Wrap)] {
+            let mut table_path = World::new(24, 18, conway.clone());
+            table_path.set_edges(x_edge, y_edge);
+            for x in 0..24 {
+                for y in 0..18 {
+                    if (x * 5 + y * y) % 7 < 3 {
+                        table_path.grid.insert(&(x, y));
+                    }
                 }
             }
+            let mut bitvec_path = table_path.clone();
+            for _ in 0..40 {
+                table_path.step();
+                bitvec_path.step_by(|world, cell| world.next_state(cell));
+                assert_eq!(table_path.grid, bitvec_path.grid);
+            }
         }
-        std::mem::swap(&mut self.grid, &mut self.swap_grid);
     }
 
-    pub fn gen(&mut self) {
-        self.grid.gen()
+    #[test]
+    fn test_url_fragment() {
+        let rules = [
+            Rule::from(gen_conway_dec()),
+            Rule::from(BigUint::from(0u32)),
+            Rule::from(gen_conway_dec() | (BigUint::from(1u32) << 511)),
+            Rule::with_mask(BigUint::from(0x8_1234u32), VON_NEUMANN),
+            Rule::with_mask(
+                BigUint::from(2u32),
+                [false, false, false, false, true, false, false, false, false],
+            ),
+        ];
+        for rule in rules.iter() {
+            let fragment = rule.to_url_fragment();
+            assert!(fragment.starts_with(URL_FRAGMENT_TAG));
+            assert!(fragment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.~".contains(c)));
+            assert_eq!(Rule::from_url_fragment(&fragment).unwrap(), *rule);
+        }
+        assert_eq!(Rule::from(gen_conway_dec()).to_url_fragment().len(), 3 + 88);
+
+        for bad in &["", "h1.", "h2.AAE", "h1.AAE=", "h1.//8", "h1._wEB"] {
+            assert!(Rule::from_url_fragment(bad).is_err(), "{}", bad);
+        }
+        for bytes in [&[][..], &[0xFF], &[1, 2], &[0xFB, 0xEF, 0xBE, 0x00]].iter() {
+            assert_eq!(decode_base64url(&encode_base64url(bytes)).unwrap(), *bytes);
+        }
+        assert_eq!(encode_base64url(&[0xFB, 0xEF, 0xBE]), "----");
+        assert_eq!(encode_base64url(b"hi"), "aGk");
     }
 
-    pub fn render(&self, canvas: &mut Widget) {
-        for x in 0..self.grid.x_bound().unwrap() {
-            for y in 0..self.grid.y_bound().unwrap() {
-                let cell = canvas.get_mut(x, y).unwrap();
-                if self.grid.contains(&(x, y)) {
-                    cell.set_ch('\u{2588}');
-                } else {
-                    cell.set_ch(' ');
+    #[test]
+    fn test_run_capped() {
+        let mut seeds = World::new(30, 30, Rule::named("Seeds").unwrap());
+        for x in 12..18 {
+            for y in 12..18 {
+                if (x * 5 + y * y) % 7 < 3 {
+                    seeds.grid.insert(&(x, y));
                 }
             }
         }
+        let e = seeds.run_capped(100, 50).unwrap_err();
+        assert!(e.population > 50);
+        assert_eq!(e.limit, 50);
+        assert_eq!(e.generation, seeds.generation());
+        assert!(e.generation < 100);
+
+        let mut blinker = World::new(5, 5, Rule::from(gen_conway_dec()));
+        for x in 1..4 {
+            blinker.grid.insert(&(x, 2));
+        }
+        assert_eq!(blinker.run_capped(20, 3), Ok(20));
+        assert_eq!(blinker.run_capped(5, 3), Ok(25));
+        assert!(blinker.run_capped(1, 2).is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use num::PrimInt;
+    #[test]
+    fn test_convex_hull() {
+        let l = Grid::from(vec!["##   ", "##   ", "##   ", "#####", "#####"]);
+        let hull: Vec<Coords> = l.convex_hull().into_iter().map(Coords::from).collect();
+        assert_eq!(hull, vec![(0, 0), (1, 0), (4, 3), (4, 4), (0, 4)]);
 
-    const EXPECTED_1082_BITS: [bool; 16] = [
-        false,
-        true,
-        false,
-        true,
-        false,
-        false,
-        false,
-        false,
-        true,
-        true,
-        true,
-        false,
-        false,
-        false,
-        false,
-        false,
-    ];
-    // 1802 = 10 + 7*(2^8), so writing in little endian byte order but writing
-    // the bits within each byte with MSB on the left, we have
-    // [00001010][00000111].
-    // As a BitVec, we want 01010000 11100000.
+        let line = Grid::from(vec!["####"]);
+        let ends: Vec<Coords> = line.convex_hull().into_iter().map(Coords::from).collect();
+        assert_eq!(ends, vec![(0, 0), (3, 0)]);
+        let pair = Grid::from(vec!["#", " #"]);
+        assert_eq!(
+            pair.convex_hull(),
+            vec![Cell { x: 0, y: 0 }, Cell { x: 1, y: 1 }]
+        );
+        assert!(Grid::new(None).convex_hull().is_empty());
+    }
 
     #[test]
-    fn test_bitvec_order() {
-        // Consider a two-byte number where the firt byte's value is 10 and
-        // the second byte's value is 7. Converting to a little endian byte
-        // array should make the 0th byte 10 and the 1th byte 7.
-        let ten_seven = BigUint::from(10 + 7 * (2.pow(8)) as u32);
-        let bytes = ten_seven.to_bytes_le();
-        assert_eq!(bytes[0], 10);
-        assert_eq!(bytes[1], 7);
-        // Now check what happens when we convert this to a BitVec. The bytes
-        // are in little endian order, but the bits within each byte are big
-        // endian:
-        // [00001010][00000111]
-        let bits = BitVec::from_bytes(&bytes);
-        let expected = vec![
-            // 0th byte representing 10
-            false,
-            false,
-            false,
-            false,
-            true,
-            false,
-            true,
-            false,
-            // 1st byte representing 7
-            false,
-            false,
-            false,
-            false,
-            false,
-            true,
-            true,
-            true,
-        ];
-        for i in 0..16 {
-            assert_eq!(bits[i], expected[i]);
-        }
+    fn test_with_mask_small_neighborhoods() {
+        let center = [false, false, false, false, true, false, false, false, false];
+        let identity = Rule::with_mask(BigUint::from(2u32), center);
+        assert_eq!(identity.bin.len(), 2);
+        assert!(!identity.output_for(0) && identity.output_for(1));
+        assert_eq!(identity.to_string(), "2");
+
+        let mut pair = center;
+        pair[3] = true;
+        let zero = Rule::with_mask(BigUint::from(0u32), pair);
+        assert_eq!(zero.bin.len(), 4);
+        assert!((0..4).all(|state| !zero.output_for(state)));
+        let last = Rule::with_mask(BigUint::from(8u32), pair);
+        assert_eq!((0..4).filter(|&state| last.output_for(state)).count(), 1);
+        assert!(last.output_for(3));
+
+        assert!(Rule::try_with_mask(BigUint::from(4u32), center).is_err());
+        assert!(Rule::try_with_mask(BigUint::from(16u32), pair).is_err());
+        assert!(Rule::try_with_mask(BigUint::from(1u32) << 40, VON_NEUMANN).is_err());
+        assert!(Rule::try_with_mask((BigUint::from(1u32) << 32) - 1u32, VON_NEUMANN).is_ok());
     }
 
-    fn gen_conway_dec() -> BigUint {
-        let mut kode = BigUint::from(0u32);
-        for state in 0..512 {
-            let mut bit_count = 0usize;
-            let current_state = (state >> 4) % 2;
-            for bit_offset in [0, 1, 2, 3, 5, 6, 7, 8].iter() {
-                bit_count += (state >> bit_offset) & 1usize;
-            }
-            let result = BigUint::from(match bit_count {
-                2 => current_state,
-                3 => 1,
-                _ => 0,
-            });
-            kode = kode + (result << state);
-        }
-        kode
+    #[test]
+    #[should_panic(expected = "too large")]
+    fn test_with_mask_oversized() {
+        Rule::with_mask(BigUint::from(1u32) << 512, MOORE);
     }
 
     #[test]
-    fn test_gen_conway_dec() {
-        let expected = "476348294852520375132009738840824718882889556\
-                        423255282629108876378472743729817205343700177\
-                        683429960362194923168607044012736510546282236\
-                        08960"
-            .parse::<BigUint>()
-            .unwrap();
-        assert_eq!(expected, gen_conway_dec());
+    fn test_huge_bounded_grid() {
+        let mut grid = Grid::bounded(usize::MAX, 2);
+        grid.insert(&(usize::MAX - 1, 1));
+        assert_eq!(grid.population(), 1);
+        assert_eq!(Grid::bounded(1 << 40, 1 << 20).population(), 0);
     }
 
     #[test]
-    fn test_grid_from_string() {
-        let grid = Grid::from(vec!["   ", "   "]);
-        let mut expected = Grid::new(None);
-        assert_eq!(grid, expected);
+    fn test_hashlife_matches_world() {
+        let glider = Grid::from(vec![" # ", "  #", "###"]);
+        let mut world = World::new(40, 40, Rule::from(gen_conway_dec()));
+        for cell in glider.grid.iter() {
+            world.grid.insert(cell);
+        }
+        let mut hashlife = HashLifeWorld::new(&world.grid, Rule::from(gen_conway_dec()));
+        for _ in 0..30 {
+            world.step();
+            hashlife.step();
+            assert_eq!(hashlife.to_grid().grid, world.grid.grid);
+        }
+        assert_eq!(hashlife.generation(), 30);
+        assert_eq!(hashlife.population(), 5);
 
-        let grid = Grid::from(vec!["#  ", "   "]);
-        expected.insert(&(0, 0));
-        assert_eq!(grid, expected);
+        let mut jumped = HashLifeWorld::new(&glider, Rule::from(gen_conway_dec()));
+        jumped.step_n(30);
+        assert_eq!(jumped.to_grid().grid, world.grid.grid);
 
-        let grid = Grid::from(vec!["#  ", " # "]);
-        expected.insert(&(1, 1));
-        assert_eq!(grid, expected);
+        // The glider moves one cell down and right every four generations.
+        let mut far = HashLifeWorld::new(&glider, Rule::from(gen_conway_dec()));
+        far.step_n(1 << 60);
+        assert_eq!(far.generation(), 1 << 60);
+        let shift = 1 << 58;
+        let expected: HashSet<Coords> = glider
+            .grid
+            .iter()
+            .map(|&(x, y)| (x + shift, y + shift))
+            .collect();
+        assert_eq!(far.to_grid().grid, expected);
     }
 
     #[test]
-    fn test_rule_dec_str_to_rule() {
-        let rule = Rule::from("1802".to_string());
-        for i in 0..16 {
-            assert_eq!(rule.bin[i], EXPECTED_1082_BITS[i]);
+    #[should_panic(expected = "2^60")]
+    fn test_hashlife_generation_limit() {
+        let mut hashlife = HashLifeWorld::new(&Grid::new(None), Rule::from(gen_conway_dec()));
+        hashlife.step_n(1 << 59);
+        hashlife.step_n(usize::MAX);
+    }
+
+    #[test]
+    fn test_hashlife_far_cells() {
+        // A block in the furthest corner allowed sits still all the way to the
+        // last generation.
+        let far = (1 << 60) - 1;
+        let mut block = Grid::new(None);
+        for &cell in [(far - 1, far - 1), (far, far - 1), (far - 1, far), (far, far)].iter() {
+            block.insert(&cell);
         }
+        let mut hashlife = HashLifeWorld::new(&block, Rule::from(gen_conway_dec()));
+        hashlife.step_n(1 << 59);
+        hashlife.step_n(1 << 59);
+        assert_eq!(hashlife.to_grid(), block);
     }
 
     #[test]
-    fn test_rule_from_bigint() {
-        let rule = Rule::from(BigUint::from(1802u32));
-        for i in 0..16 {
-            assert_eq!(rule.bin[i], EXPECTED_1082_BITS[i]);
+    #[should_panic(expected = "2^60")]
+    fn test_hashlife_coordinate_limit() {
+        let mut grid = Grid::new(None);
+        grid.insert(&(0, 1 << 60));
+        HashLifeWorld::new(&grid, Rule::from(gen_conway_dec()));
+    }
+
+    // A benchmark rather than a test; run with
+    // `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_outer_totalistic_table() {
+        const SIDE: usize = 256;
+        const GENERATIONS: usize = 32;
+        let mut table_path = World::new(SIDE, SIDE, Rule::from(gen_conway_dec()));
+        for x in 0..SIDE {
+            for y in 0..SIDE {
+                if (x * 7 + y * 13) % 3 != 0 {
+                    table_path.grid.insert(&(x, y));
+                }
+            }
+        }
+        let mut bitvec_path = table_path.clone();
+
+        let start = Instant::now();
+        for _ in 0..GENERATIONS {
+            table_path.step();
+        }
+        println!("Table:  {:?}", start.elapsed());
+
+        let start = Instant::now();
+        for _ in 0..GENERATIONS {
+            bitvec_path.step_by(|world, cell| world.next_state(cell));
+        }
+        println!("BitVec: {:?}", start.elapsed());
+
+        assert_eq!(table_path.grid, bitvec_path.grid);
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_hashlife_blinker_field() {
+        const SIDE: usize = 256;
+        const GENERATIONS: usize = 1024;
+        let mut world = World::new(SIDE, SIDE, Rule::from(gen_conway_dec()));
+        for y in (2..SIDE - 2).step_by(4) {
+            for x in (2..SIDE - 2).step_by(4) {
+                for x in x - 1..x + 2 {
+                    world.grid.insert(&(x, y));
+                }
+            }
+        }
+        let mut hashlife = HashLifeWorld::new(&world.grid, Rule::from(gen_conway_dec()));
+
+        let start = Instant::now();
+        for _ in 0..GENERATIONS {
+            world.step();
         }
+        println!("World:         {:?}", start.elapsed());
+
+        let start = Instant::now();
+        hashlife.step_n(GENERATIONS);
+        println!("HashLifeWorld: {:?}", start.elapsed());
+
+        assert_eq!(hashlife.to_grid().grid, world.grid.grid);
     }
 
     #[test]
@@ -316,48 +6045,12 @@ mod tests {
         // 0#< look here
         //  ^
         grid.insert(&(0, 0));
-        assert_eq!(get_state(&grid, &(0, 0)), 16); // 2^4
+        assert_eq!(get_state(&grid, &(0, 0), &MOORE, DEAD_EDGES), 16); // 2^4
         //  01
         // 0#-< look here
         // 1-#
         //  ^
         grid.insert(&(1, 1));
-        assert_eq!(get_state(&grid, &(0, 0)), 272); // 2^4 + 2^8
-    }
-}
-
-fn get_state(grid: &Grid, cell: &Cell) -> usize {
-    let (x, y) = (cell.0, cell.1);
-    let mut val = 0;
-    // We now build up an integer representation of the state centered at cell.
-    // We iterate over neighboring cells: dx and dy go over [0, 1, 2] where
-    //   0 means "minus one", so "left" for x or "up" for y
-    //   1 means same row (for x) or column (for y).
-    //   2 means "plus one", so "right" for x or "down" for y.
-    // Therefore, for a given dx and dy, the coordinates of the neighbor are
-    // (x+dx-1, y+dy-1). However, if we're at an edge, these coordinates might
-    // take us off the grid. This shows up as a failure to do the subtraction
-    // because getting a negative number means we're off the grid. We check
-    // for this failure with checked_sub and return false if the call returns
-    // None.
-    //
-    // TODO: replace "integer representation" with canonical name once we pick
-    // one.
-    for dx in 0..3 {
-        for dy in 0..3 {
-            if match (x + dx).checked_sub(1) {
-                None => false,
-                Some(xx) => {
-                    match (y + dy).checked_sub(1) {
-                        None => false,
-                        Some(yy) => grid.contains(&(xx, yy)),
-                    }
-                }
-            }
-            {
-                val += 1 << (dx + (3 * dy));
-            }
-        }
+        assert_eq!(get_state(&grid, &(0, 0), &MOORE, DEAD_EDGES), 272); // 2^4 + 2^8
     }
-    val
 }
@@ -1,13 +1,24 @@
 use std::time::Duration;
 use std::env;
+use std::process;
 
 use rustty::{Event, HasSize, Terminal};
 use rustty::ui::{Alignable, HorizontalAlign, VerticalAlign, Widget};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <rule number>", args[0]);
+        process::exit(2);
+    }
     let rule_arg = &args[1]; // 0th arg is program name
-    let rule = hemoglobin::Rule::from(rule_arg.to_owned());
+    let rule = match rule_arg.parse::<hemoglobin::Rule>() {
+        Ok(rule) => rule,
+        Err(e) => {
+            eprintln!("{}: {}", rule_arg, e);
+            process::exit(2);
+        }
+    };
 
     //Create terminal and canvas
     let mut term = Terminal::new().unwrap();
@@ -28,8 +39,7 @@ fn main() {
             delay = 20;
         }
         while let Some(Event::Key(c)) =
-            term.get_event(Some(Duration::from_millis(delay)).unwrap())
-                .unwrap()
+            term.get_event(Duration::from_millis(delay)).unwrap()
         {
             match c {
                 'q' => break 'rendering,
@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+
+use num::bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{Cell, Coords, EdgeMode, Error, NeighborhoodMask, Rule, Style, World};
+
+/// Everything an interactive app needs to resume exactly where it left off:
+/// the world, the part of it on screen and how it's drawn
+pub struct Session {
+    pub world: World,
+    /// The cell drawn at the top left corner of the canvas
+    pub viewport_origin: Cell,
+    pub style: Style,
+}
+
+/// A session as it's written to disk
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    /// The rule number, in decimal
+    rule: String,
+    mask: NeighborhoodMask,
+    width: usize,
+    height: usize,
+    cells: Vec<Coords>,
+    generation: usize,
+    last_activity: usize,
+    edges: (EdgeMode, EdgeMode),
+    viewport_origin: Cell,
+    style: Style,
+}
+
+impl Session {
+    /// Writes the session to `path` as JSON
+    ///
+    /// Which cells the world has visited isn't kept.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let (width, height) = self.world.dimensions();
+        let file = SessionFile {
+            rule: self.world.rule.to_string(),
+            mask: self.world.rule.mask,
+            width,
+            height,
+            cells: self.world.grid.iter_sorted().collect(),
+            generation: self.world.generation,
+            last_activity: self.world.last_activity,
+            edges: self.world.edges,
+            viewport_origin: self.viewport_origin,
+            style: self.style,
+        };
+        let json = serde_json::to_string(&file).map_err(|e| Error::Parse(e.to_string()))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Reads a session written by `save` from `path`
+    pub fn load(path: &Path) -> Result<Session, Error> {
+        let json = fs::read_to_string(path)?;
+        let file: SessionFile =
+            serde_json::from_str(&json).map_err(|e| Error::Parse(e.to_string()))?;
+        let number = file
+            .rule
+            .parse::<BigUint>()
+            .map_err(|e| Error::Parse(format!("invalid rule number: {}", e)))?;
+        let rule = Rule::try_with_mask(number, file.mask)?;
+        let mut world = World::try_new(file.width, file.height, rule)?;
+        for cell in file.cells {
+            world.grid.try_insert(cell)?;
+        }
+        world.generation = file.generation;
+        world.last_activity = file.last_activity;
+        world.edges = file.edges;
+        Ok(Session {
+            world,
+            viewport_origin: file.viewport_origin,
+            style: file.style,
+        })
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use crate::Transition;
+
+/// An unbounded grid that stretches infinitely in every direction, so
+/// patterns can move past the origin into negative coordinates
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SignedGrid {
+    cells: HashSet<(i64, i64)>,
+}
+
+impl SignedGrid {
+    /// Returns an empty grid
+    pub fn new() -> Self {
+        SignedGrid::default()
+    }
+
+    /// Makes `cell` live
+    pub fn insert(&mut self, cell: (i64, i64)) {
+        self.cells.insert(cell);
+    }
+
+    /// Returns whether `cell` is live
+    pub fn contains(&self, cell: (i64, i64)) -> bool {
+        self.cells.contains(&cell)
+    }
+
+    /// Returns the number of live cells
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Advances the grid by one generation under `rule`
+    ///
+    /// Only live cells and their neighbors are looked at, so this works for
+    /// any pattern on the infinite plane.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rule` brings cells with no live neighbors to life, as that
+    /// would fill the infinite plane.
+    pub fn step<T: Transition>(&mut self, rule: &T) {
+        assert!(!rule.next(false, 0), "can't run rules with B0 on the infinite plane");
+        let mut candidates = HashSet::new();
+        for &(x, y) in self.cells.iter() {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    candidates.insert((x + dx, y + dy));
+                }
+            }
+        }
+        let next = candidates
+            .into_iter()
+            .filter(|&(x, y)| {
+                // Same layout as get_state: bit dx + 3 * dy for the neighbor
+                // at (x + dx - 1, y + dy - 1).
+                let mut state = 0;
+                for dx in 0..3 {
+                    for dy in 0..3 {
+                        if self.contains((x + dx - 1, y + dy - 1)) {
+                            state |= 1 << (dx + 3 * dy);
+                        }
+                    }
+                }
+                rule.next(self.contains((x, y)), state)
+            })
+            .collect();
+        self.cells = next;
+    }
+}